@@ -0,0 +1,28 @@
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+
+fn home() -> Node {
+    div!(h1!("Home"), link!("/counter", "Go to the counter")).into()
+}
+
+fn counter() -> Node {
+    let value = StateAtomic::new(0isize);
+
+    div!(
+        link!("/", "Home"),
+        h1!("Value ", value.clone()),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+#[valerie(start)]
+pub fn run() {
+    App::new()
+        .push("/", home)
+        .push("/counter", counter)
+        .start("/")
+        .render();
+}
@@ -0,0 +1,11 @@
+use valerie::ssr::Document;
+
+fn main() {
+    let dir = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from("./static"));
+
+    static_site::app()
+        .generate(&dir, &Document::new().title("Valerie"))
+        .expect("Cannot write the static site");
+}
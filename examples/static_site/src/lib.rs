@@ -0,0 +1,47 @@
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::router::Mode;
+
+fn home() -> Node {
+    div!(
+        h1!("Valerie"),
+        p!("Rust front-end framework for building web apps."),
+        link!("/docs", "Read the docs")
+    )
+    .into()
+}
+
+fn docs() -> Node {
+    let value = StateAtomic::new(0isize);
+
+    div!(
+        link!("/", "Home"),
+        h1!("Docs"),
+        p!("The counter below works once the page is hydrated."),
+        h2!("Value ", value.clone()),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+fn not_found() -> Node {
+    div!(h1!("Page not found"), link!("/", "Home")).into()
+}
+
+pub fn app() -> App {
+    let mut app = App::new();
+    app.push("/", home)
+        .push("/docs", docs)
+        .not_found(not_found)
+        .mode(Mode::History);
+
+    app
+}
+
+#[valerie(start)]
+pub fn run() {
+    // The app is rendered from scratch if the markup is not the same.
+    app().hydrate().ok();
+}
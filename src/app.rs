@@ -1,20 +1,136 @@
 use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, io, path::Path};
 
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::backend::{Backend, Dom, DomNode};
 use crate::function;
+use crate::html;
+use crate::hydrate::{self, HydrationError};
+use crate::router::{self, Guard, Mode, Params};
+use crate::ssr::{self, Document};
+use crate::state::StateTrait;
+use crate::task::{self, Task};
 // use crate::state;
 
 // pub type Globals = Option<state::StateTrait>;
 
-struct Route(&'static str, Box<dyn Fn() -> crate::Node>);
+/// The maximum number of redirects followed for a navigation.
+const REDIRECTS: usize = 16;
+
+type Page = Box<dyn Fn(&Params) -> Option<Content>>;
+
+/// The content of a page, which is either ready or still loading.
+enum Content {
+    Ready(crate::Node),
+    Pending(Pin<Box<dyn Future<Output = crate::Node>>>),
+}
+
+/// The future of a pending page, which attaches the tasks spawned while it is polled
+/// to the page it resolves to, so that they are stopped when the page is torn down.
+/// They are stopped right away if it is dropped before resolving.
+struct Scoped {
+    future: Pin<Box<dyn Future<Output = crate::Node>>>,
+    tasks: Vec<Task>,
+}
+
+impl Future for Scoped {
+    type Output = crate::Node;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut poll = Poll::Pending;
+        let tasks = task::scope(|| poll = this.future.as_mut().poll(cx));
+        this.tasks.extend(tasks);
+        if let Poll::Ready(page) = &poll {
+            this.tasks.drain(..).for_each(|x| page.add_task(x));
+        }
+        poll
+    }
+}
+
+impl Drop for Scoped {
+    fn drop(&mut self) {
+        self.tasks.iter().for_each(Task::abort);
+    }
+}
+
+struct Route {
+    pattern: &'static str,
+    page: Page,
+}
+
+struct Layout {
+    pattern: &'static str,
+    layout: Box<dyn Fn(crate::Node) -> crate::Node>,
+}
+
+struct Guarded {
+    pattern: &'static str,
+    guard: Box<dyn Fn(&str) -> Guard>,
+}
+
+struct Mounted {
+    layout: usize,
+    node: crate::Node,
+    outlet: crate::Node,
+}
+
+/// An element inside which an `App` can be mounted
+///
+/// Implemented for CSS selectors, `web_sys::Element` and the nodes of the DOM.
+pub trait Root {
+    /// Get the node of the element.
+    fn node(&self) -> DomNode;
+}
+
+impl Root for &str {
+    fn node(&self) -> DomNode {
+        Dom::query_selector(self).expect("No element matches the selector")
+    }
+}
+
+impl Root for DomNode {
+    fn node(&self) -> DomNode {
+        self.clone()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Root for web_sys::Element {
+    fn node(&self) -> DomNode {
+        self.clone().into()
+    }
+}
 
 /// The `App` struct for creating an App
 ///
-/// *Routing is not currently implemented.*
+/// Routes are matched against `location.hash` or `location.pathname` depending on the `Mode`,
+/// and the page is swapped whenever the path changes.
 #[derive(Default)]
 pub struct App {
     routes: Vec<Route>,
+    layouts: Vec<Layout>,
+    guards: Vec<Guarded>,
+    redirects: Vec<(&'static str, &'static str)>,
+    not_found: Option<Box<dyn Fn() -> crate::Node>>,
+    loading: Option<Box<dyn Fn() -> crate::Node>>,
     start: Option<&'static str>,
+    mode: Mode,
+    root: Option<DomNode>,
+    path: RefCell<Option<String>>,
+    navigation: Cell<usize>,
+    mounted: RefCell<Vec<Mounted>>,
+    page: RefCell<Option<crate::Node>>,
 }
 
 impl App {
@@ -25,47 +141,519 @@ impl App {
 
         Self {
             routes: Vec::new(),
+            layouts: Vec::new(),
+            guards: Vec::new(),
+            redirects: Vec::new(),
+            not_found: None,
+            loading: None,
             start: None,
+            mode: Mode::Hash,
+            root: None,
+            path: RefCell::new(None),
+            navigation: Cell::new(0),
+            mounted: RefCell::new(Vec::new()),
+            page: RefCell::new(None),
         }
     }
 
     /// Push routes inside the `App` struct.
+    /// The routes are matched in the order they are pushed.
     pub fn push(
         &mut self,
         route: &'static str,
         component: impl Fn() -> crate::Node + 'static,
     ) -> &mut Self {
-        if self.routes.iter().any(|x| x.0 == route) {
-            panic!("Same routes for two pages");
-        }
+        self.push_params(route, move |_| Some(component()))
+    }
 
-        self.routes.push(Route(route, Box::new(component)));
+    /// Push a route with dynamic segments inside the `App` struct.
+    ///
+    /// The page is passed the `Params` matched from the path. If it returns `None`,
+    /// eg: when a parameter can not be parsed, the path is matched against the next routes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn post(user: u32, post: u32) -> Node {
+    ///     h1!("Post ", post, " by user ", user).into()
+    /// }
+    ///
+    /// fn file(path: String) -> Node {
+    ///     h1!("File ", path).into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push_params("/users/:id/posts/:post", |params| {
+    ///             Some(post(params.get("id")?, params.get("post")?))
+    ///         })
+    ///         .push_params("/files/*path", |params| Some(file(params.get("path")?)))
+    ///         .render();
+    /// }
+    /// ```
+    pub fn push_params(
+        &mut self,
+        route: &'static str,
+        component: impl Fn(&Params) -> Option<crate::Node> + 'static,
+    ) -> &mut Self {
+        self.routes.push(Route {
+            pattern: route,
+            page: Box::new(move |x| component(x).map(Content::Ready)),
+        });
         self.start = Some(route);
         self
     }
 
+    /// Push routes with pages that have to be loaded asynchronously inside the `App` struct.
+    ///
+    /// The page specified using `loading` is rendered until the future resolves.
+    /// If the path changes before that, the resolved page is discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// async fn report() -> Node {
+    ///     let rows = 10; // Fetch the data here
+    ///     div!(h1!("Report"), p!(rows, " rows")).into()
+    /// }
+    ///
+    /// fn loading() -> Node {
+    ///     p!("Loading...").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push_async("/report", report)
+    ///         .loading(loading)
+    ///         .render();
+    /// }
+    /// ```
+    pub fn push_async<F>(
+        &mut self,
+        route: &'static str,
+        component: impl Fn() -> F + 'static,
+    ) -> &mut Self
+    where
+        F: Future<Output = crate::Node> + 'static,
+    {
+        self.push_async_params(route, move |_| Some(component()))
+    }
+
+    /// Push a route with dynamic segments and a page that has to be loaded asynchronously
+    /// inside the `App` struct.
+    ///
+    /// Works the same way as `push_params` and `push_async`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// async fn user(id: u32) -> Node {
+    ///     let name = "Valerie"; // Fetch the user here
+    ///     h1!("User ", id, ": ", name).into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push_async_params("/users/:id", |params| Some(user(params.get("id")?)))
+    ///         .render();
+    /// }
+    /// ```
+    pub fn push_async_params<F>(
+        &mut self,
+        route: &'static str,
+        component: impl Fn(&Params) -> Option<F> + 'static,
+    ) -> &mut Self
+    where
+        F: Future<Output = crate::Node> + 'static,
+    {
+        self.routes.push(Route {
+            pattern: route,
+            page: Box::new(move |x| Some(Content::Pending(Box::pin(component(x)?)))),
+        });
+        self.start = Some(route);
+        self
+    }
+
+    /// Push a layout for all the routes starting with `route`.
+    ///
+    /// The layout is passed an outlet `Node` inside which the pages are rendered.
+    /// Navigating between pages under the same layout only replaces the content of the outlet,
+    /// so the rest of the layout and its states stay in place.
+    /// Layouts can be nested, eg: a layout for `/dashboard` and one for `/dashboard/settings`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn dashboard(outlet: Node) -> Node {
+    ///     div!(
+    ///         ul!(li!("Overview"), li!("Settings")),
+    ///         outlet
+    ///     )
+    ///     .into()
+    /// }
+    ///
+    /// fn overview() -> Node {
+    ///     h1!("Overview").into()
+    /// }
+    ///
+    /// fn settings() -> Node {
+    ///     h1!("Settings").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .layout("/dashboard", dashboard)
+    ///         .push("/dashboard", overview)
+    ///         .push("/dashboard/settings", settings)
+    ///         .render();
+    /// }
+    /// ```
+    pub fn layout(
+        &mut self,
+        route: &'static str,
+        layout: impl Fn(crate::Node) -> crate::Node + 'static,
+    ) -> &mut Self {
+        self.layouts.push(Layout {
+            pattern: route,
+            layout: Box::new(layout),
+        });
+        self
+    }
+
+    /// Push a guard for all the routes starting with `route`.
+    ///
+    /// The guard is passed the path being navigated to, and decides whether to allow,
+    /// deny or redirect the navigation. A denied navigation stays on the current page.
+    /// Guards are checked in the order they are pushed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use valerie::router::Guard;
+    /// # use wasm_bindgen_test::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    ///
+    /// fn admin() -> Node {
+    ///     h1!("Admin").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     let admin_user = StateAtomic::new(false);
+    ///
+    ///     App::new()
+    ///         .push("/", home)
+    ///         .push("/admin", admin)
+    ///         .guard("/admin", move |_| {
+    ///             if admin_user.value() {
+    ///                 Guard::Allow
+    ///             } else {
+    ///                 Guard::Deny
+    ///             }
+    ///         })
+    ///         .render();
+    /// }
+    /// ```
+    pub fn guard(
+        &mut self,
+        route: &'static str,
+        guard: impl Fn(&str) -> Guard + 'static,
+    ) -> &mut Self {
+        self.guards.push(Guarded {
+            pattern: route,
+            guard: Box::new(guard),
+        });
+        self
+    }
+
+    /// Redirect the paths matching the route `from` to the route `to`.
+    ///
+    /// Dynamic segments of `to` are filled with the parameters of the same name in `from`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn user(id: u32) -> Node {
+    ///     h1!("User ", id).into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push_params("/users/:id", |params| Some(user(params.get("id")?)))
+    ///         .redirect("/profile/:id", "/users/:id")
+    ///         .render();
+    /// }
+    /// ```
+    pub fn redirect(&mut self, from: &'static str, to: &'static str) -> &mut Self {
+        self.redirects.push((from, to));
+        self
+    }
+
+    /// Specify the page rendered when the path does not match any route.
+    /// If it is not specified, the starting route is rendered instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    ///
+    /// fn not_found() -> Node {
+    ///     h1!("Page not found").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push("/", home)
+    ///         .not_found(not_found)
+    ///         .render();
+    /// }
+    /// ```
+    pub fn not_found(&mut self, component: impl Fn() -> crate::Node + 'static) -> &mut Self {
+        self.not_found = Some(Box::new(component));
+        self
+    }
+
+    /// Specify the page rendered while an asynchronous page is loading.
+    /// If it is not specified, the previous page stays until the page is loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// async fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    ///
+    /// fn loading() -> Node {
+    ///     p!("Loading...").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push_async("/", home)
+    ///         .loading(loading)
+    ///         .render();
+    /// }
+    /// ```
+    pub fn loading(&mut self, component: impl Fn() -> crate::Node + 'static) -> &mut Self {
+        self.loading = Some(Box::new(component));
+        self
+    }
+
     /// Specify the starting route.
+    /// It is rendered when the path is empty, or when the path does not match any route
+    /// and no page is specified using `not_found`.
     pub fn start(&mut self, start: &'static str) -> &mut Self {
         self.start = Some(start);
         self
     }
 
+    /// Specify the routing `Mode`. `Mode::Hash` is used by default.
+    /// Every `App` keeps its own mode, which is used by the links rendered inside it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use valerie::router::Mode;
+    /// # use wasm_bindgen_test::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .mode(Mode::History)
+    ///         .push("/", home)
+    ///         .render();
+    /// }
+    /// ```
+    pub fn mode(&mut self, mode: Mode) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Mount the `App` inside an element instead of the body.
+    /// The content of the element is replaced by the `App`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .mount_to("#app")
+    ///         .push("/", home)
+    ///         .render();
+    /// }
+    /// ```
+    pub fn mount_to(&mut self, root: impl Root) -> &mut Self {
+        self.root = Some(root.node());
+        self
+    }
+
+    /// Navigate to a path of the rendered `App`.
+    /// The `Mode` is the one of the `App` in which the calling event listener was created.
+    ///
+    /// In `Mode::Hash` this sets `location.hash`, and in `Mode::History`
+    /// this calls `history.pushState` and renders the matching route.
+    /// The entry is added to the history only if the guards allow the navigation,
+    /// and a redirected path is added instead of the `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// button!("About").on_event("click", (), |_, _| {
+    ///     App::navigate("/about");
+    /// })
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn navigate(path: impl AsRef<str>) {
+        router::navigate(Mode::current(), path.as_ref());
+    }
+
     /// Render the `App`.
+    ///
+    /// The route matching the path is rendered, and the page is replaced every time
+    /// the path changes i.e. on `hashchange` in `Mode::Hash` and on `popstate` in `Mode::History`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    ///
+    /// fn about() -> Node {
+    ///     h1!("About").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push("/", home)
+    ///         .push("/about", about)
+    ///         .start("/")
+    ///         .render();
+    /// }
+    /// ```
     pub fn render(&mut self) {
-        let route = self
-            .routes
-            .iter()
-            .position(|x| x.0 == self.start.unwrap())
-            .unwrap();
-        if let Some(x) = function::body().first_child() {
-            function::body()
-                .replace_child(&self.routes[route].1(), &x)
-                .unwrap();
-        } else {
-            function::body()
-                .append_child(&self.routes[route].1())
-                .unwrap();
-        }
+        #[cfg(feature = "debug")]
+        console_error_panic_hook::set_once();
+
+        let app = Rc::new(core::mem::take(self));
+        app.route();
+        app.listen();
+    }
+
+    /// Render the `App` by hydrating the markup rendered on the server,
+    /// instead of creating the nodes again.
+    ///
+    /// The nodes of the layouts and the page are claimed when they are mounted,
+    /// and the event listeners and states are attached to them.
+    /// If the markup is not the same as the one created by the `App`,
+    /// the `App` is rendered like `render` does, and the mismatch is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push("/", home)
+    ///         .start("/")
+    ///         .mount_to("#app")
+    ///         .hydrate()
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn hydrate(&mut self) -> Result<(), HydrationError> {
+        #[cfg(feature = "debug")]
+        console_error_panic_hook::set_once();
+
+        let app = Rc::new(core::mem::take(self));
+        let ((), mismatch) = hydrate::scope(|| app.route());
+
+        let result = match mismatch {
+            Some(e) => {
+                if let Some(x) = app.page.borrow_mut().take() {
+                    x.teardown();
+                }
+                app.mounted
+                    .borrow_mut()
+                    .drain(..)
+                    .for_each(|x| x.node.teardown());
+                app.path.replace(None);
+                app.route();
+                Err(e)
+            }
+            None => Ok(()),
+        };
+
+        app.listen();
+        result
     }
 
     /// Render a single page.
@@ -90,12 +678,513 @@ impl App {
         #[cfg(feature = "debug")]
         console_error_panic_hook::set_once();
 
-        if let Some(x) = function::body().first_child() {
-            function::body().replace_child(&function, &x).unwrap();
+        mount_root(None, &function);
+        function.forget();
+    }
+
+    /// Render a single page inside an element instead of the body.
+    /// The content of the element is replaced by the page.
+    ///
+    /// Can be used multiple times to render independent pages inside different elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn cart() -> Node {
+    ///     p!("Items in cart ", StateAtomic::new(0)).into()
+    /// }
+    ///
+    /// fn search() -> Node {
+    ///     input!("search").into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::render_single_to(cart(), "#cart");
+    ///     App::render_single_to(search(), "#search");
+    /// }
+    /// ```
+    pub fn render_single_to(function: crate::Node, root: impl Root) {
+        #[cfg(feature = "debug")]
+        console_error_panic_hook::set_once();
+
+        mount_root(Some(&root.node()), &function);
+        function.forget();
+    }
+
+    /// Render a single page inside an element by hydrating the markup rendered on the server,
+    /// instead of creating the nodes again.
+    ///
+    /// If the markup is not the same as the one created by `function`,
+    /// the page is rendered like `render_single_to` does, and the mismatch is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn ui() -> Node {
+    ///     let value = StateAtomic::new(0);
+    ///
+    ///     div!(
+    ///         h1!("Value ", value.clone()),
+    ///         button!("Add 1").on_event("click", value, |x, _| *x += 1)
+    ///     )
+    ///     .into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     // The body contains `<div id="app">` followed by `valerie::ssr::render_to_string(ui())`
+    ///     App::hydrate_single(ui, "#app").unwrap();
+    /// }
+    /// ```
+    pub fn hydrate_single<F>(function: F, root: impl Root) -> Result<(), HydrationError>
+    where
+        F: Fn() -> crate::Node,
+    {
+        #[cfg(feature = "debug")]
+        console_error_panic_hook::set_once();
+
+        let root = root.node();
+        let node = function();
+        let ((), mismatch) = hydrate::scope(|| mount_root(Some(&root), &node));
+        node.forget();
+        mismatch.map_or(Ok(()), Err)
+    }
+
+    /// Render the page for the `path` with its layouts to an HTML string.
+    ///
+    /// An asynchronous page is rendered if it is ready right away,
+    /// otherwise the `loading` page is rendered.
+    /// The redirects and guards are not applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.push("/", home)
+    ///     .layout("/", |outlet| div!(p!("Header"), outlet).into());
+    ///
+    /// assert_eq!(
+    ///     app.render_to_string("/").unwrap(),
+    ///     r#"<div><p>Header</p><div data-outlet=""><h1>Home</h1></div></div>"#
+    /// );
+    /// ```
+    pub fn render_to_string(&self, path: impl AsRef<str>) -> Option<String> {
+        let _mode = Mode::enter(Some(self.mode));
+        router::location().put(path.as_ref().to_string());
+
+        let page = self.page(path.as_ref())?;
+        let page = self.ready(page)?;
+
+        Some(ssr::render_to_string(self.wrap(path.as_ref(), page)))
+    }
+
+    /// The path rendered for the requested `path`, after following the redirects and the guards.
+    ///
+    /// Returns `None` if a guard denies the `path`, or if it is redirected too many times.
+    /// Can be used on the server to answer with a redirect,
+    /// before rendering the page using `render_to_string`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// use valerie::router::Guard;
+    ///
+    /// fn user(id: u32) -> Node {
+    ///     h1!("User ", id).into()
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.push_params("/users/:id", |params| Some(user(params.get("id")?)))
+    ///     .redirect("/profile/:id", "/users/:id")
+    ///     .guard("/users/0", |_| Guard::Deny);
+    ///
+    /// assert_eq!(app.resolve("/profile/7").as_deref(), Some("/users/7"));
+    /// assert_eq!(app.resolve("/profile/0"), None);
+    /// ```
+    pub fn resolve(&self, path: impl AsRef<str>) -> Option<String> {
+        let mut path = path.as_ref().to_string();
+        for _ in 0..=REDIRECTS {
+            if let Some(to) = self
+                .redirects
+                .iter()
+                .find_map(|x| Some(Params::matches(x.0, &path)?.fill(x.1)))
+            {
+                path = to;
+                continue;
+            }
+
+            let guard = self
+                .guards
+                .iter()
+                .filter(|x| Params::matches_prefix(x.pattern, &path).is_some())
+                .map(|x| (x.guard)(&path))
+                .find(|x| x != &Guard::Allow)
+                .unwrap_or(Guard::Allow);
+
+            match guard {
+                Guard::Allow => return Some(path),
+                Guard::Deny => return None,
+                Guard::Redirect(to) => path = to,
+            }
+        }
+
+        None
+    }
+
+    /// Generate a static site, by rendering every route without parameters
+    /// into an `index.html` inside the `dir`, eg: `/about` is rendered into `about/index.html`.
+    /// The `not_found` page is rendered into `404.html`.
+    ///
+    /// The routes which are redirected or have a guard are skipped. The guards are not run,
+    /// as they decide using the state of the browser, which does not exist while generating.
+    /// Use `Mode::History`, so that the routes are matched against the path of the files,
+    /// and `hydrate` the `App` in the browser.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// use valerie::router::Mode;
+    /// use valerie::ssr::Document;
+    ///
+    /// fn home() -> Node {
+    ///     h1!("Home").into()
+    /// }
+    ///
+    /// fn about() -> Node {
+    ///     h1!("About").into()
+    /// }
+    ///
+    /// App::new()
+    ///     .push("/", home)
+    ///     .push("/about", about)
+    ///     .mode(Mode::History)
+    ///     .generate("./static", &Document::new().title("Docs"))
+    ///     .unwrap();
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn generate(&self, dir: impl AsRef<Path>, document: &Document) -> io::Result<()> {
+        let _mode = Mode::enter(Some(self.mode));
+        for route in self.routes.iter() {
+            let static_route = router::is_static(route.pattern);
+            let redirected = self
+                .redirects
+                .iter()
+                .any(|x| Params::matches(x.0, route.pattern).is_some());
+            let guarded = self
+                .guards
+                .iter()
+                .any(|x| Params::matches_prefix(x.pattern, route.pattern).is_some());
+            if !static_route || redirected || guarded {
+                continue;
+            }
+
+            router::location().put(route.pattern.to_string());
+            let page = match (route.page)(&Params::default()).and_then(|x| self.ready(x)) {
+                Some(x) => x,
+                None => continue,
+            };
+            let html = ssr::render_to_string(self.wrap(route.pattern, page));
+
+            let mut path = dir.as_ref().to_path_buf();
+            path.extend(route.pattern.split('/').filter(|x| !x.is_empty()));
+            fs::create_dir_all(&path)?;
+            fs::write(path.join("index.html"), document.render(&html))?;
+        }
+
+        if let Some(x) = &self.not_found {
+            // The page is shown for the paths matching no route, so no link is active.
+            router::location().put(String::from("/404.html"));
+            let html = ssr::render_to_string(x());
+            fs::create_dir_all(dir.as_ref())?;
+            fs::write(dir.as_ref().join("404.html"), document.render(&html))?;
+        }
+
+        Ok(())
+    }
+
+    /// Listen to the changes of the path.
+    fn listen(self: &Rc<Self>) {
+        let app = Rc::clone(self);
+        let callback = Closure::wrap(Box::new(move || app.route()) as Box<dyn FnMut()>);
+        function::window()
+            .add_event_listener_with_callback(self.mode.event(), callback.as_ref().unchecked_ref())
+            .unwrap();
+        callback.forget();
+    }
+
+    /// Render the route for the path passed to `App::navigate`, or else the one in the URL.
+    fn route(self: &Rc<Self>) {
+        let _mode = Mode::enter(Some(self.mode));
+        let navigation = router::take_navigation();
+        let requested = navigation.clone().unwrap_or_else(|| self.mode.path());
+        if self.path.borrow().as_ref() == Some(&requested) {
+            return;
+        }
+
+        let (path, page) = match self.resolve(&requested) {
+            Some(path) => {
+                let page = self.page(&path);
+                (path, page)
+            }
+            None => {
+                let current = self.path.borrow().clone();
+                match current {
+                    // A denied navigation is not added to the history,
+                    // and a denied entry of the history is replaced by the current path.
+                    Some(current) => {
+                        if navigation.is_none() {
+                            self.mode.replace(&current);
+                        }
+                        return;
+                    }
+                    None => {
+                        let page = self.not_found.as_ref().map(|x| Content::Ready(x()));
+                        (requested.clone(), page)
+                    }
+                }
+            }
+        };
+
+        if navigation.is_some() && self.path.borrow().as_ref() != Some(&path) {
+            self.mode.push(&path);
+        }
+        self.show(&requested, path, page);
+
+        // `history.pushState` does not fire `popstate`, which the states bound to
+        // the query parameters listen to.
+        if navigation.is_some() && self.mode == Mode::History {
+            function::window()
+                .dispatch_event(&web_sys::Event::new(Mode::History.event()).unwrap())
+                .unwrap();
+        }
+    }
+
+    /// The page for the `path`, falling back to the starting route or the `not_found` page.
+    /// The tasks spawned while creating it are stopped when it is torn down.
+    fn page(&self, path: &str) -> Option<Content> {
+        let mut page = None;
+        let tasks = task::scope(|| page = self.find_page(path));
+        match page {
+            Some(Content::Ready(x)) => {
+                tasks.into_iter().for_each(|task| x.add_task(task));
+                Some(Content::Ready(x))
+            }
+            Some(Content::Pending(future)) => {
+                Some(Content::Pending(Box::pin(Scoped { future, tasks })))
+            }
+            None => {
+                tasks.iter().for_each(Task::abort);
+                None
+            }
+        }
+    }
+
+    fn find_page(&self, path: &str) -> Option<Content> {
+        let start = || {
+            let route = self.routes.iter().find(|x| Some(x.pattern) == self.start)?;
+            (route.page)(&Params::default())
+        };
+        let not_found = || self.not_found.as_ref().map(|x| Content::Ready(x()));
+
+        self.routes
+            .iter()
+            .filter_map(|x| (x.page)(&Params::matches(x.pattern, path)?))
+            .next()
+            .or_else(|| {
+                if router::depth(path) == 0 {
+                    start().or_else(not_found)
+                } else {
+                    not_found().or_else(start)
+                }
+            })
+    }
+
+    /// Show the `page` for the `path`, replacing the `requested` path if they are not the same.
+    fn show(self: &Rc<Self>, requested: &str, path: String, page: Option<Content>) {
+        if path != requested {
+            self.mode.replace(&path);
+            if self.path.borrow().as_ref() == Some(&path) {
+                return;
+            }
+        }
+
+        if let Some(page) = page {
+            let navigation = self.navigation.get() + 1;
+            self.navigation.set(navigation);
+
+            router::location().put(path.clone());
+            self.mount_layouts(&path);
+            match page {
+                Content::Ready(page) => self.mount_page(page),
+                Content::Pending(page) => {
+                    if let Some(loading) = &self.loading {
+                        self.mount_page(loading());
+                    }
+
+                    let app = Rc::clone(self);
+                    task::execute(async move {
+                        let page = page.await;
+                        if app.navigation.get() == navigation {
+                            app.mount_page(page);
+                        } else {
+                            page.teardown();
+                        }
+                    });
+                }
+            }
+        }
+        self.path.replace(Some(path));
+    }
+
+    fn mount_page(&self, page: crate::Node) {
+        self.mount(page.clone());
+        if let Some(old) = self.page.replace(Some(page)) {
+            old.teardown();
+        }
+    }
+
+    /// The page if it is ready right away, or the `loading` page.
+    fn ready(&self, page: Content) -> Option<crate::Node> {
+        match page {
+            Content::Ready(x) => Some(x),
+            Content::Pending(x) => {
+                let page = Rc::new(RefCell::new(None));
+                let page_move = Rc::clone(&page);
+                task::execute(async move {
+                    page_move.replace(Some(x.await));
+                });
+                task::run();
+
+                let page = page.borrow_mut().take();
+                page.or_else(|| self.loading.as_ref().map(|x| x()))
+            }
+        }
+    }
+
+    /// Wrap the `page` inside the layouts matching the `path`.
+    fn wrap(&self, path: &str, page: crate::Node) -> crate::Node {
+        let mut root = None;
+        let mut outlet: Option<crate::Node> = None;
+        for layout in self.layouts(path) {
+            let new = new_outlet();
+            let node = crate::Node::scoped(|| (self.layouts[layout].layout)(new.clone()));
+            match &outlet {
+                Some(x) => x.push_child(node),
+                None => root = Some(node),
+            }
+            outlet = Some(new);
+        }
+
+        match (root, outlet) {
+            (Some(root), Some(outlet)) => {
+                outlet.push_child(page);
+                root
+            }
+            _ => page,
+        }
+    }
+
+    /// The layouts matching the `path`, from the outermost to the innermost.
+    fn layouts(&self, path: &str) -> Vec<usize> {
+        let mut layouts = (0..self.layouts.len())
+            .filter(|&x| Params::matches_prefix(self.layouts[x].pattern, path).is_some())
+            .collect::<Vec<_>>();
+        layouts.sort_by_key(|&x| router::depth(self.layouts[x].pattern));
+        layouts
+    }
+
+    /// Mount the layouts matching the `path`, keeping the ones which are already mounted.
+    fn mount_layouts(&self, path: &str) {
+        let layouts = self.layouts(path);
+
+        let common = self
+            .mounted
+            .borrow()
+            .iter()
+            .zip(layouts.iter())
+            .take_while(|(x, &y)| x.layout == y)
+            .count();
+        let removed = self.mounted.borrow_mut().split_off(common);
+        removed.iter().for_each(|x| x.node.teardown());
+
+        for &layout in &layouts[common..] {
+            let outlet = new_outlet();
+            let node = crate::Node::scoped(|| (self.layouts[layout].layout)(outlet.clone()));
+            self.mount(node.clone());
+            self.mounted.borrow_mut().push(Mounted {
+                layout,
+                node,
+                outlet,
+            });
+        }
+    }
+
+    /// Mount the `node` inside the innermost outlet, or the root if there are no layouts.
+    fn mount(&self, node: crate::Node) {
+        if let Some(x) = self.mounted.borrow().last() {
+            x.outlet.replace_children(node);
         } else {
-            function::body().append_child(&function).unwrap();
+            mount_root(self.root.as_ref(), &node);
         }
+    }
+}
 
-        function.forget();
+/// Create an outlet for a layout.
+fn new_outlet() -> crate::Node {
+    let outlet: crate::Node = crate::Tag::<html::elements::Div>::new().into();
+    outlet.set_attr(hydrate::OUTLET, "");
+    outlet
+}
+
+/// Mount the `node` replacing the content of the `root`,
+/// or replacing the first child of the body if there is no `root`.
+/// While hydrating, the `node` claims the nodes rendered there instead.
+fn mount_root(root: Option<&DomNode>, node: &crate::Node) {
+    if hydrate::active() {
+        let parent = root.cloned().unwrap_or_else(function::body);
+        let mut rendered = hydrate::rendered(&parent);
+        // The page is expected to be the first node of the body.
+        if root.is_none() {
+            rendered.truncate(1);
+        }
+        hydrate::claim(&parent, &rendered, 0, node);
+    }
+
+    match root {
+        Some(root) if Dom::parent(node).as_ref() == Some(root) => (),
+        Some(root) => {
+            for x in Dom::children(root) {
+                Dom::remove_child(root, &x);
+            }
+            Dom::insert_before(root, node, None);
+        }
+        None => {
+            let body = function::body();
+            match Dom::children(&body).first() {
+                Some(x) if x == &**node => (),
+                Some(x) => {
+                    Dom::insert_before(&body, node, Some(x));
+                    Dom::remove_child(&body, x);
+                }
+                None => Dom::insert_before(&body, node, None),
+            }
+        }
     }
 }
@@ -0,0 +1,325 @@
+use alloc::boxed::Box;
+use alloc::rc::{Rc, Weak};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+
+use crate::task;
+
+use super::{Backend, EventTrait};
+
+type Callback = Rc<RefCell<Box<dyn FnMut(MemoryEvent)>>>;
+
+std::thread_local! {
+    static BODY: MemoryNode = MemoryNode::new(Kind::Element {
+        tag: String::from("body"),
+        attributes: Vec::new(),
+        listeners: Vec::new(),
+    });
+}
+
+/// An in-memory DOM, used where there is no browser
+///
+/// Supports elements, text, attributes, classes, children and event listeners.
+/// The futures updating the nodes are run when an event is dispatched,
+/// or when `flush` is called.
+pub struct Memory;
+
+/// A node of the in-memory DOM
+#[derive(Clone)]
+pub struct MemoryNode {
+    data: Rc<RefCell<Data>>,
+}
+
+struct Data {
+    kind: Kind,
+    parent: Weak<RefCell<Data>>,
+    children: Vec<MemoryNode>,
+}
+
+enum Kind {
+    Element {
+        tag: String,
+        attributes: Vec<(String, String)>,
+        listeners: Vec<(String, MemoryListener)>,
+    },
+    Text(String),
+}
+
+/// An event listener of the in-memory DOM
+#[derive(Clone)]
+pub struct MemoryListener {
+    callback: Callback,
+}
+
+/// An event of the in-memory DOM
+#[derive(Clone, Default)]
+pub struct MemoryEvent {
+    name: String,
+    default_prevented: Rc<Cell<bool>>,
+    modified: bool,
+}
+
+impl MemoryEvent {
+    /// Create an event with the `name`, like `click`.
+    pub fn new(name: impl AsRef<str>) -> Self {
+        Self {
+            name: name.as_ref().to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Mark the event as a click with a modifier key pressed.
+    pub fn with_modifier(mut self) -> Self {
+        self.modified = true;
+        self
+    }
+
+    /// The name of the event.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl MemoryNode {
+    fn new(kind: Kind) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(Data {
+                kind,
+                parent: Weak::new(),
+                children: Vec::new(),
+            })),
+        }
+    }
+
+    fn text_content(&self) -> String {
+        let data = self.data.borrow();
+        match &data.kind {
+            Kind::Element { .. } => data.children.iter().map(Self::text_content).collect(),
+            Kind::Text(x) => x.clone(),
+        }
+    }
+}
+
+impl PartialEq for MemoryNode {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl Eq for MemoryNode {}
+
+impl Memory {
+    /// Dispatch an event with the `name` on the `node`.
+    /// See `dispatch_event`.
+    pub fn dispatch(node: &MemoryNode, name: impl AsRef<str>) -> bool {
+        Self::dispatch_event(node, MemoryEvent::new(name))
+    }
+
+    /// Dispatch the `event` on the `node`.
+    ///
+    /// The listeners of the `node` and then the ones of its ancestors are called,
+    /// and the futures updating the nodes are run.
+    /// Returns `false` if the default action was prevented.
+    pub fn dispatch_event(node: &MemoryNode, event: MemoryEvent) -> bool {
+        let mut target = Some(node.clone());
+        while let Some(node) = target {
+            let listeners = match &node.data.borrow().kind {
+                Kind::Element { listeners, .. } => listeners
+                    .iter()
+                    .filter(|x| x.0 == event.name)
+                    .map(|x| x.1.clone())
+                    .collect(),
+                Kind::Text(_) => Vec::new(),
+            };
+            for x in listeners {
+                (x.callback.borrow_mut())(event.clone());
+            }
+
+            target = Self::parent(&node);
+        }
+
+        Self::flush();
+        !event.default_prevented()
+    }
+
+    /// Run the futures updating the nodes, after a state is changed outside of an event.
+    pub fn flush() {
+        task::run();
+    }
+
+    /// The text of the `node` and all its children.
+    pub fn text_content(node: &MemoryNode) -> String {
+        Self::flush();
+        node.text_content()
+    }
+}
+
+impl Backend for Memory {
+    type Node = MemoryNode;
+    type Event = MemoryEvent;
+    type Listener = MemoryListener;
+
+    fn create_element(tag: &str) -> Self::Node {
+        MemoryNode::new(Kind::Element {
+            tag: tag.to_string(),
+            attributes: Vec::new(),
+            listeners: Vec::new(),
+        })
+    }
+
+    fn create_text(text: &str) -> Self::Node {
+        MemoryNode::new(Kind::Text(text.to_string()))
+    }
+
+    fn body() -> Self::Node {
+        BODY.with(Clone::clone)
+    }
+
+    /// Only simple selectors are supported, like `#id`, `.class` or `tag`.
+    fn query_selector(selector: &str) -> Option<Self::Node> {
+        let matches = |node: &Self::Node| {
+            if let Some(id) = selector.strip_prefix('#') {
+                Self::attributes(node)
+                    .iter()
+                    .any(|x| x.0 == "id" && x.1 == id)
+            } else if let Some(class) = selector.strip_prefix('.') {
+                Self::attributes(node)
+                    .iter()
+                    .any(|x| x.0 == "class" && x.1.split_whitespace().any(|x| x == class))
+            } else {
+                Self::tag(node).as_deref() == Some(selector)
+            }
+        };
+
+        let mut stack = Self::children(&Self::body());
+        stack.reverse();
+        while let Some(node) = stack.pop() {
+            if matches(&node) {
+                return Some(node);
+            }
+            stack.extend(Self::children(&node).into_iter().rev());
+        }
+        None
+    }
+
+    fn tag(node: &Self::Node) -> Option<String> {
+        match &node.data.borrow().kind {
+            Kind::Element { tag, .. } => Some(tag.clone()),
+            Kind::Text(_) => None,
+        }
+    }
+
+    fn is_text(node: &Self::Node) -> bool {
+        matches!(node.data.borrow().kind, Kind::Text(_))
+    }
+
+    fn text(node: &Self::Node) -> String {
+        match &node.data.borrow().kind {
+            Kind::Element { .. } => String::new(),
+            Kind::Text(x) => x.clone(),
+        }
+    }
+
+    fn set_text(node: &Self::Node, text: &str) {
+        if let Kind::Text(x) = &mut node.data.borrow_mut().kind {
+            *x = text.to_string();
+        }
+    }
+
+    fn attributes(node: &Self::Node) -> Vec<(String, String)> {
+        match &node.data.borrow().kind {
+            Kind::Element { attributes, .. } => attributes.clone(),
+            Kind::Text(_) => Vec::new(),
+        }
+    }
+
+    fn set_attribute(node: &Self::Node, key: &str, value: &str) {
+        if let Kind::Element { attributes, .. } = &mut node.data.borrow_mut().kind {
+            match attributes.iter_mut().find(|x| x.0 == key) {
+                Some(x) => x.1 = value.to_string(),
+                None => attributes.push((key.to_string(), value.to_string())),
+            }
+        }
+    }
+
+    fn remove_attribute(node: &Self::Node, key: &str) {
+        if let Kind::Element { attributes, .. } = &mut node.data.borrow_mut().kind {
+            attributes.retain(|x| x.0 != key);
+        }
+    }
+
+    fn value(node: &Self::Node) -> String {
+        Self::attributes(node)
+            .into_iter()
+            .find(|x| x.0 == "value")
+            .map(|x| x.1)
+            .unwrap_or_default()
+    }
+
+    fn set_value(node: &Self::Node, value: &str) {
+        Self::set_attribute(node, "value", value);
+    }
+
+    fn parent(node: &Self::Node) -> Option<Self::Node> {
+        node.data
+            .borrow()
+            .parent
+            .upgrade()
+            .map(|data| MemoryNode { data })
+    }
+
+    fn children(node: &Self::Node) -> Vec<Self::Node> {
+        node.data.borrow().children.clone()
+    }
+
+    fn insert_before(parent: &Self::Node, child: &Self::Node, reference: Option<&Self::Node>) {
+        if let Some(x) = Self::parent(child) {
+            Self::remove_child(&x, child);
+        }
+
+        let mut data = parent.data.borrow_mut();
+        let index = reference
+            .and_then(|reference| data.children.iter().position(|x| x == reference))
+            .unwrap_or_else(|| data.children.len());
+        data.children.insert(index, child.clone());
+        child.data.borrow_mut().parent = Rc::downgrade(&parent.data);
+    }
+
+    fn remove_child(parent: &Self::Node, child: &Self::Node) {
+        parent.data.borrow_mut().children.retain(|x| x != child);
+        child.data.borrow_mut().parent = Weak::new();
+    }
+
+    fn listener(callback: Box<dyn FnMut(Self::Event)>) -> Self::Listener {
+        MemoryListener {
+            callback: Rc::new(RefCell::new(callback)),
+        }
+    }
+
+    fn add_event_listener(node: &Self::Node, event: &str, listener: &Self::Listener) {
+        if let Kind::Element { listeners, .. } = &mut node.data.borrow_mut().kind {
+            listeners.push((event.to_string(), listener.clone()));
+        }
+    }
+
+    fn remove_event_listener(node: &Self::Node, event: &str, listener: &Self::Listener) {
+        if let Kind::Element { listeners, .. } = &mut node.data.borrow_mut().kind {
+            listeners.retain(|x| x.0 != event || !Rc::ptr_eq(&x.1.callback, &listener.callback));
+        }
+    }
+}
+
+impl EventTrait for MemoryEvent {
+    fn default_prevented(&self) -> bool {
+        self.default_prevented.get()
+    }
+
+    fn prevent_default(&self) {
+        self.default_prevented.set(true);
+    }
+
+    fn modified(&self) -> bool {
+        self.modified
+    }
+}
@@ -0,0 +1,131 @@
+//!
+//! The DOM backends the `Node`s are built on.
+//!
+//! The backend is chosen at compile time. `WebSys` uses the DOM of the browser,
+//! and is used when compiling to `wasm32`. `Memory` keeps the nodes in memory,
+//! and is used everywhere else, so that the pages can be rendered on the server
+//! and the components can be tested using `cargo test`.
+//!
+//! # Examples
+//!
+//! ```
+//! # use valerie::prelude::*;
+//! # use valerie::prelude::components::*;
+//! use valerie::backend::Memory;
+//!
+//! let value = StateAtomic::new(0);
+//! let button: Node = button!("Add 1")
+//!     .on_event("click", value.clone(), |x, _| *x += 1)
+//!     .into();
+//! let page: Node = div!(p!(value), button.clone()).into();
+//!
+//! Memory::dispatch(&button, "click");
+//! assert_eq!(Memory::text_content(&page), "1Add 1");
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use memory::{Memory, MemoryEvent, MemoryListener, MemoryNode};
+pub use web::WebSys;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod memory;
+mod web;
+
+/// The backend used by the `Node`s, `WebSys` in the browser and `Memory` everywhere else.
+#[cfg(target_arch = "wasm32")]
+pub type Dom = WebSys;
+/// The backend used by the `Node`s, `WebSys` in the browser and `Memory` everywhere else.
+#[cfg(not(target_arch = "wasm32"))]
+pub type Dom = Memory;
+
+/// The node type of the backend in use.
+pub type DomNode = <Dom as Backend>::Node;
+
+/// The operations on the DOM used by `Node`.
+pub trait Backend {
+    /// A node, which is either an element or text.
+    type Node: Clone + PartialEq;
+
+    /// The event passed on to the listeners.
+    type Event: EventTrait;
+
+    /// An event listener which can be removed.
+    type Listener;
+
+    /// Create an element with the `tag`.
+    fn create_element(tag: &str) -> Self::Node;
+
+    /// Create a text node.
+    fn create_text(text: &str) -> Self::Node;
+
+    /// The `body` element of the document.
+    fn body() -> Self::Node;
+
+    /// The first element in the document matching the CSS `selector`.
+    fn query_selector(selector: &str) -> Option<Self::Node>;
+
+    /// The tag of an element, `None` for other nodes.
+    fn tag(node: &Self::Node) -> Option<String>;
+
+    /// Check whether the node is a text node.
+    fn is_text(node: &Self::Node) -> bool;
+
+    /// The text of a text node.
+    fn text(node: &Self::Node) -> String;
+
+    /// Set the text of a text node.
+    fn set_text(node: &Self::Node, text: &str);
+
+    /// The attributes of an element.
+    fn attributes(node: &Self::Node) -> Vec<(String, String)>;
+
+    /// Set an attribute of an element.
+    fn set_attribute(node: &Self::Node, key: &str, value: &str);
+
+    /// Remove an attribute of an element.
+    fn remove_attribute(node: &Self::Node, key: &str);
+
+    /// The value of an input element.
+    fn value(node: &Self::Node) -> String;
+
+    /// Set the value of an input element.
+    fn set_value(node: &Self::Node, value: &str);
+
+    /// The parent of the node.
+    fn parent(node: &Self::Node) -> Option<Self::Node>;
+
+    /// The children of the node.
+    fn children(node: &Self::Node) -> Vec<Self::Node>;
+
+    /// Insert the `child` before the `reference`, or at the end if there is no `reference`.
+    fn insert_before(parent: &Self::Node, child: &Self::Node, reference: Option<&Self::Node>);
+
+    /// Remove the `child` from the `parent`.
+    fn remove_child(parent: &Self::Node, child: &Self::Node);
+
+    /// Create a listener from the `callback`.
+    fn listener(callback: Box<dyn FnMut(Self::Event)>) -> Self::Listener;
+
+    /// Add an event listener to the node.
+    fn add_event_listener(node: &Self::Node, event: &str, listener: &Self::Listener);
+
+    /// Remove an event listener from the node.
+    fn remove_event_listener(node: &Self::Node, event: &str, listener: &Self::Listener);
+}
+
+/// The operations on an event used by the `Tag`s.
+pub trait EventTrait {
+    /// Check whether the default action was prevented.
+    fn default_prevented(&self) -> bool;
+
+    /// Prevent the default action of the event.
+    fn prevent_default(&self);
+
+    /// Check whether the event is a click with a button other than the main one,
+    /// or with a modifier key pressed.
+    fn modified(&self) -> bool;
+}
@@ -0,0 +1,149 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::function;
+
+use super::{Backend, EventTrait};
+
+/// The browser DOM, using `web_sys`
+pub struct WebSys;
+
+impl Backend for WebSys {
+    type Node = web_sys::Node;
+    type Event = web_sys::Event;
+    type Listener = Closure<dyn FnMut(web_sys::Event)>;
+
+    fn create_element(tag: &str) -> Self::Node {
+        function::document()
+            .create_element(tag)
+            .expect("Cannot create the element needed")
+            .into()
+    }
+
+    fn create_text(text: &str) -> Self::Node {
+        function::document().create_text_node(text).into()
+    }
+
+    fn body() -> Self::Node {
+        function::document()
+            .body()
+            .expect("Document should have a body")
+            .into()
+    }
+
+    fn query_selector(selector: &str) -> Option<Self::Node> {
+        function::document()
+            .query_selector(selector)
+            .expect("Invalid selector")
+            .map(Into::into)
+    }
+
+    fn tag(node: &Self::Node) -> Option<String> {
+        node.dyn_ref::<web_sys::Element>()
+            .map(|x| x.tag_name().to_lowercase())
+    }
+
+    fn is_text(node: &Self::Node) -> bool {
+        node.node_type() == web_sys::Node::TEXT_NODE
+    }
+
+    fn text(node: &Self::Node) -> String {
+        node.node_value().unwrap_or_default()
+    }
+
+    fn set_text(node: &Self::Node, text: &str) {
+        node.set_node_value(Some(text));
+    }
+
+    fn attributes(node: &Self::Node) -> Vec<(String, String)> {
+        let element = match node.dyn_ref::<web_sys::Element>() {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        element
+            .get_attribute_names()
+            .iter()
+            .filter_map(|x| x.as_string())
+            .map(|x| {
+                let value = element.get_attribute(&x).unwrap_or_default();
+                (x, value)
+            })
+            .collect()
+    }
+
+    fn set_attribute(node: &Self::Node, key: &str, value: &str) {
+        node.unchecked_ref::<web_sys::Element>()
+            .set_attribute(key, value)
+            .unwrap();
+    }
+
+    fn remove_attribute(node: &Self::Node, key: &str) {
+        node.unchecked_ref::<web_sys::Element>()
+            .remove_attribute(key)
+            .unwrap();
+    }
+
+    fn value(node: &Self::Node) -> String {
+        node.unchecked_ref::<web_sys::HtmlDataElement>().value()
+    }
+
+    fn set_value(node: &Self::Node, value: &str) {
+        node.unchecked_ref::<web_sys::HtmlDataElement>()
+            .set_value(value);
+    }
+
+    fn parent(node: &Self::Node) -> Option<Self::Node> {
+        node.parent_node()
+    }
+
+    fn children(node: &Self::Node) -> Vec<Self::Node> {
+        let nodes = node.child_nodes();
+        (0..nodes.length()).filter_map(|x| nodes.item(x)).collect()
+    }
+
+    fn insert_before(parent: &Self::Node, child: &Self::Node, reference: Option<&Self::Node>) {
+        parent.insert_before(child, reference).unwrap();
+    }
+
+    fn remove_child(parent: &Self::Node, child: &Self::Node) {
+        parent.remove_child(child).unwrap();
+    }
+
+    fn listener(callback: Box<dyn FnMut(Self::Event)>) -> Self::Listener {
+        Closure::wrap(callback)
+    }
+
+    fn add_event_listener(node: &Self::Node, event: &str, listener: &Self::Listener) {
+        node.add_event_listener_with_callback(event, listener.as_ref().unchecked_ref())
+            .unwrap();
+    }
+
+    fn remove_event_listener(node: &Self::Node, event: &str, listener: &Self::Listener) {
+        node.remove_event_listener_with_callback(event, listener.as_ref().unchecked_ref())
+            .unwrap();
+    }
+}
+
+impl EventTrait for web_sys::Event {
+    fn default_prevented(&self) -> bool {
+        web_sys::Event::default_prevented(self)
+    }
+
+    fn prevent_default(&self) {
+        web_sys::Event::prevent_default(self)
+    }
+
+    fn modified(&self) -> bool {
+        match self.dyn_ref::<web_sys::MouseEvent>() {
+            Some(x) => {
+                x.button() != 0 || x.meta_key() || x.ctrl_key() || x.shift_key() || x.alt_key()
+            }
+            None => false,
+        }
+    }
+}
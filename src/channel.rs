@@ -1,34 +1,51 @@
-use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use core::convert::From;
-use core::fmt::Display;
+use core::fmt;
 use core::ops::Deref;
 
-/// A wrapper around `Arc<String>`
+/// A wrapper around `Arc<T>`
 ///
 /// The format of the message that is passed from `Sender` to `Receiver`.
 ///
-/// Rather than passing the value inside the state as it is and converting it to string in the
-/// destination, the value is converted to the `Channel` type and then passed on to the receivers.
-#[derive(Clone)]
-pub struct Channel {
-    stream: Arc<String>,
+/// The value inside the state is passed on to the receivers as it is, and is shared by all of them.
+/// It is converted to a string only in the destinations showing it, like text nodes and attributes.
+pub struct Channel<T> {
+    value: Arc<T>,
 }
 
-impl<T> From<T> for Channel
-where
-    T: Display,
-{
+impl<T> From<T> for Channel<T> {
     fn from(x: T) -> Self {
+        Self { value: Arc::new(x) }
+    }
+}
+
+impl<T> Channel<T> {
+    /// Pass on the value shared with the state, without cloning it.
+    pub(crate) fn shared(value: Arc<T>) -> Self {
+        Self { value }
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
         Self {
-            stream: Arc::new(x.to_string()),
+            value: Arc::clone(&self.value),
         }
     }
 }
 
-impl Deref for Channel {
-    type Target = String;
+impl<T> Deref for Channel<T> {
+    type Target = T;
     fn deref(&self) -> &Self::Target {
-        &self.stream
+        &self.value
+    }
+}
+
+impl<T> fmt::Display for Channel<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
     }
 }
@@ -1,8 +1,6 @@
 use alloc::string::ToString;
 use core::fmt::Display;
 
-use wasm_bindgen::JsCast;
-
 use crate::function::create_text_element;
 
 /// `Component` trait
@@ -15,6 +13,6 @@ where
     T: Display,
 {
     fn from(x: T) -> Self {
-        Self::new(create_text_element(x.to_string()).unchecked_into())
+        Self::new(create_text_element(x.to_string()))
     }
 }
@@ -0,0 +1,229 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+
+use crate::backend::{Backend, Dom, DomNode};
+use crate::node::PENDING;
+
+/// The attribute marking the outlets of the layouts.
+pub(crate) const OUTLET: &str = "data-outlet";
+
+local! {
+    static HYDRATION: RefCell<Option<Hydration>> = RefCell::new(None);
+}
+
+/// The error returned when the markup rendered on the server
+/// is not the same as the one created on the client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HydrationError {
+    path: String,
+    expected: String,
+    found: String,
+}
+
+impl HydrationError {
+    fn new(parent: &DomNode, index: usize, expected: String, found: String) -> Self {
+        Self {
+            path: format!("{}, child {}", path(parent), index),
+            expected,
+            found,
+        }
+    }
+
+    /// Where the mismatch is, as the selector of the element rendered on the server
+    /// and the index of the child inside it, eg: `div#app > div > h1:nth-child(1), child 1`.
+    /// The whitespace between the elements is not counted in the index.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl fmt::Display for HydrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Hydration mismatch at {}: expected {}, found {}",
+            self.path, self.expected, self.found
+        )
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::error::Error for HydrationError {}
+
+/// The state of the hydration, stopped by the first mismatch.
+struct Hydration {
+    error: Option<HydrationError>,
+}
+
+/// What is done with a created node once the whole tree is known to match.
+enum Claim {
+    /// Use the node rendered on the server.
+    Node(crate::Node, DomNode),
+    /// Insert the created node inside the parent, before the reference.
+    Insert(DomNode, crate::Node, Option<DomNode>),
+}
+
+/// Run `func`, letting the nodes mounted by it claim the nodes rendered on the server,
+/// instead of inserting the ones created.
+///
+/// The nodes are claimed when they are mounted, so they are matched in the order of the tree,
+/// whatever the order they were created in.
+/// After a mismatch, the nodes are inserted as usual for the rest of `func`.
+pub(crate) fn scope<T>(func: impl FnOnce() -> T) -> (T, Option<HydrationError>) {
+    let old = HYDRATION.with(|x| x.borrow_mut().replace(Hydration { error: None }));
+    let value = func();
+    let hydration = HYDRATION.with(|x| core::mem::replace(&mut *x.borrow_mut(), old));
+
+    (value, hydration.and_then(|x| x.error))
+}
+
+/// Check whether the nodes are being hydrated.
+pub(crate) fn active() -> bool {
+    HYDRATION.with(|x| x.borrow().as_ref().is_some_and(|x| x.error.is_none()))
+}
+
+/// The nodes rendered on the server inside the `parent`, without the whitespace between them.
+/// The comments separating the text nodes are removed.
+pub(crate) fn rendered(parent: &DomNode) -> Vec<DomNode> {
+    let mut nodes = Vec::new();
+    for x in Dom::children(parent) {
+        if Dom::tag(&x).is_none() && !Dom::is_text(&x) {
+            Dom::remove_child(parent, &x);
+        } else if Dom::tag(&x).is_some() || !is_whitespace(&Dom::text(&x)) {
+            nodes.push(x);
+        }
+    }
+    nodes
+}
+
+/// Claim the `rendered` nodes inside the `parent` from the `index`, for the `node` and its children.
+///
+/// Nothing is claimed if they are not the same,
+/// and `false` is returned after keeping the mismatch.
+pub(crate) fn claim(
+    parent: &DomNode,
+    rendered: &[DomNode],
+    index: usize,
+    node: &crate::Node,
+) -> bool {
+    let mut claims = Vec::new();
+    let nodes = core::slice::from_ref(node);
+    if let Err(e) = pair(parent, rendered, index, nodes, &mut claims) {
+        HYDRATION.with(|x| {
+            if let Some(x) = x.borrow_mut().as_mut() {
+                x.error.get_or_insert(e);
+            }
+        });
+        return false;
+    }
+
+    for claim in claims {
+        match claim {
+            Claim::Node(node, x) => node.claim(x),
+            Claim::Insert(parent, node, reference) => {
+                Dom::insert_before(&parent, &node, reference.as_ref())
+            }
+        }
+    }
+    true
+}
+
+/// Match the `nodes` with the `rendered` nodes inside the `parent` from the `index`,
+/// and their children.
+fn pair(
+    parent: &DomNode,
+    rendered: &[DomNode],
+    mut index: usize,
+    nodes: &[crate::Node],
+    claims: &mut Vec<Claim>,
+) -> Result<(), HydrationError> {
+    let mut rendered = rendered.get(index..).unwrap_or(&[]).iter().peekable();
+    for node in nodes {
+        // An empty text node is not rendered on the server.
+        let next = rendered.peek().copied();
+        if Dom::is_text(node) && Dom::text(node).is_empty() && !next.is_some_and(Dom::is_text) {
+            claims.push(Claim::Insert(parent.clone(), node.clone(), next.cloned()));
+            continue;
+        }
+
+        let x = match rendered.next() {
+            Some(x) if Dom::tag(x) == Dom::tag(node) => x,
+            x => {
+                return Err(HydrationError::new(
+                    parent,
+                    index,
+                    describe(Some(node.as_ref())),
+                    describe(x),
+                ))
+            }
+        };
+        index += 1;
+        // The children of an outlet are claimed when the page is mounted inside it,
+        // and the ones of a pending node are kept until the future resolves.
+        if !has_attribute(node, OUTLET) && !has_attribute(node, PENDING) {
+            pair(x, &self::rendered(x), 0, &node.children(), claims)?;
+        }
+        claims.push(Claim::Node(node.clone(), x.clone()));
+    }
+
+    match rendered.next() {
+        Some(x) => Err(HydrationError::new(
+            parent,
+            index,
+            String::from("nothing"),
+            describe(Some(x)),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Check whether the `text` is only the whitespace between the elements.
+/// The empty text nodes are kept, as they are only found in the nodes created on the client.
+fn is_whitespace(text: &str) -> bool {
+    !text.is_empty() && text.trim().is_empty()
+}
+
+fn has_attribute(node: &DomNode, name: &str) -> bool {
+    Dom::attributes(node).iter().any(|x| x.0 == name)
+}
+
+/// The selector of the `node`, from the closest ancestor with an id, or else from the root.
+fn path(node: &DomNode) -> String {
+    let mut selectors = Vec::new();
+    let mut node = Some(node.clone());
+    while let Some(x) = node {
+        let tag = Dom::tag(&x).unwrap_or_default();
+        let parent = Dom::parent(&x);
+        let id = Dom::attributes(&x).into_iter().find(|x| x.0 == "id");
+        if let Some((_, id)) = id {
+            selectors.push(format!("{}#{}", tag, id));
+            break;
+        }
+
+        let siblings = parent.as_ref().map(Dom::children).unwrap_or_default();
+        let elements: Vec<_> = siblings.iter().filter(|x| Dom::tag(x).is_some()).collect();
+        match elements.iter().position(|y| **y == x) {
+            Some(i) if elements.len() > 1 => {
+                selectors.push(format!("{}:nth-child({})", tag, i + 1))
+            }
+            _ => selectors.push(tag),
+        }
+        node = parent;
+    }
+
+    selectors.reverse();
+    selectors.join(" > ")
+}
+
+fn describe(node: Option<&DomNode>) -> String {
+    match node {
+        Some(node) => match Dom::tag(node) {
+            Some(tag) => format!("<{}>", tag),
+            None => format!("text {:?}", Dom::text(node)),
+        },
+        None => String::from("nothing"),
+    }
+}
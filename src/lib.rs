@@ -140,44 +140,59 @@
 //! runtime and you want a stack trace.
 
 extern crate alloc;
+#[cfg(not(target_arch = "wasm32"))]
+extern crate std;
+
+#[macro_use]
+mod local;
 
 mod app;
 mod channel;
 mod component;
 mod function;
+mod hydrate;
 mod macros;
 mod node;
 mod tag;
+mod task;
 mod value;
 
+pub mod backend;
 /// Contains the HTML Tags
 pub mod html;
+/// Contains the types used for routing
+pub mod router;
+/// Contains the functions for rendering on the server
+pub mod ssr;
 /// Contains the structs for defining States
 pub mod state;
 
-pub use app::App;
+pub use app::{App, Root};
 pub use channel::Channel;
 pub use component::Component;
+pub use hydrate::HydrationError;
 pub use node::Node;
 pub use tag::Tag;
 pub use value::Value;
 
 /// The `prelude` module
 pub mod prelude {
+    pub use crate::task::execute;
     pub use wasm_bindgen;
     pub use wasm_bindgen::prelude::wasm_bindgen as valerie;
-    pub use wasm_bindgen_futures::spawn_local as execute;
     pub use web_sys;
 
     pub use crate::html;
     pub use crate::{App, Component, Node, Tag};
 
-    pub use state::{StateAtomic, StateMutex, StateTrait, StateVec};
+    pub use state::{StateAtomic, StateMap, StateMutex, StateTrait, StateVec};
 
     use crate::*;
 
     /// The components module imports all the macros for the Tags
     pub mod components {
-        pub use crate::{br, button, div, h1, h2, h3, h4, h5, h6, img, input, li, ol, p, span, ul};
+        pub use crate::{
+            br, button, div, h1, h2, h3, h4, h5, h6, img, input, li, link, ol, p, span, ul,
+        };
     }
 }
@@ -0,0 +1,36 @@
+/// Declare statics local to the thread, which are used through `with` like `std::thread_local!`.
+///
+/// Outside the browser they are thread locals, so that every thread rendering pages
+/// has its own. The browser runs the wasm module on a single thread,
+/// so they are plain statics there, and `std` is not needed.
+macro_rules! local {
+    ($(static $name:ident: $t:ty = $init:expr;)+) => {
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread_local! {
+            $(static $name: $t = const { $init };)+
+        }
+        $(
+            #[cfg(target_arch = "wasm32")]
+            static $name: $crate::local::Local<$t> = $crate::local::Local::new($init);
+        )+
+    };
+}
+
+/// A static used only by the thread running the wasm module.
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Local<T>(T);
+
+// Without the `atomics` feature, wasm32 has a single thread, so the value is never shared.
+#[cfg(all(target_arch = "wasm32", not(target_feature = "atomics")))]
+unsafe impl<T> Sync for Local<T> {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> Local<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub(crate) fn with<R>(&'static self, func: impl FnOnce(&T) -> R) -> R {
+        func(&self.0)
+    }
+}
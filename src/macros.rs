@@ -207,6 +207,20 @@ macro_rules! input {
     };
 }
 
+/// `a` element linking to a path of the `App`
+#[macro_export]
+macro_rules! link {
+    ( $path:expr $( , $x:expr )* ) => {
+        {
+            let mut link = $crate::Tag::<$crate::html::elements::A>::new().link($path);
+            $(
+                link = link.push($x);
+            )*
+            link
+        }
+    };
+}
+
 /// `img` element
 #[macro_export]
 macro_rules! img {
@@ -3,63 +3,331 @@ use alloc::rc::Rc;
 use alloc::string::{String, ToString};
 use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cell::OnceCell;
+use core::future::Future;
 use core::ops::{Deref, DerefMut};
 
 use parking_lot::Mutex;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
 
-use crate::component;
+use crate::backend::{Backend, Dom, DomNode};
+use crate::component::{self, Component};
+use crate::function;
+use crate::hydrate;
+use crate::task::{self, Task};
 
-type CallbackMap = BTreeMap<String, Rc<Closure<dyn FnMut()>>>;
+/// The attribute marking the nodes waiting for a future.
+pub(crate) const PENDING: &str = "data-pending";
 
-/// A wrapper for `web_sys::Node`
+type CallbackMap = BTreeMap<String, Rc<<Dom as Backend>::Listener>>;
+
+/// A wrapper for a node of the DOM, `web_sys::Node` in the browser
 #[derive(Clone)]
 pub struct Node {
-    pub(crate) node: web_sys::Node,
+    pub(crate) node: Rc<Slot>,
     id: Arc<Mutex<Option<String>>>,
     class: Arc<Mutex<BTreeSet<String>>>,
     attr: Arc<Mutex<BTreeMap<String, String>>>,
     children: Arc<Mutex<Vec<Node>>>,
     callbacks: Arc<Mutex<CallbackMap>>,
+    tasks: Arc<Mutex<Vec<Task>>>,
+}
+
+/// The node of the DOM shared by the clones of a `Node`,
+/// which is the node rendered on the server once it is claimed while hydrating.
+#[derive(Clone)]
+pub(crate) struct Slot {
+    created: DomNode,
+    claimed: OnceCell<DomNode>,
+}
+
+impl Deref for Slot {
+    type Target = DomNode;
+
+    fn deref(&self) -> &Self::Target {
+        self.claimed.get().unwrap_or(&self.created)
+    }
 }
 
 impl Node {
-    pub(crate) fn new(node: web_sys::Node) -> Self {
+    pub(crate) fn new(node: DomNode) -> Self {
         Self {
-            node,
+            node: Rc::new(Slot {
+                created: node,
+                claimed: OnceCell::new(),
+            }),
             id: Arc::new(Mutex::new(None)),
             class: Arc::new(Mutex::new(BTreeSet::new())),
             attr: Arc::new(Mutex::new(BTreeMap::new())),
             children: Arc::new(Mutex::new(Vec::new())),
             callbacks: Arc::new(Mutex::new(BTreeMap::new())),
+            tasks: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Create a `Node` showing the `placeholder` until the `future` resolves,
+    /// and then the component returned by the `future`.
+    ///
+    /// The nodes are kept in a `div` with `display: contents`,
+    /// which has a `data-pending` attribute while the `future` is running.
+    /// When rendered using `ssr::render_to_stream`, the placeholder is sent first,
+    /// and the component is sent as soon as the `future` resolves.
+    /// While hydrating, the markup rendered on the server is kept instead of the placeholder
+    /// until the `future` resolves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// use valerie::backend::Memory;
+    ///
+    /// let page = Node::from_future(|| "Loading...", async { h1!("Loaded") });
+    /// assert_eq!(Memory::text_content(&page), "Loaded");
+    /// ```
+    pub fn from_future<P, U, F, C>(placeholder: P, future: F) -> Self
+    where
+        P: FnOnce() -> U,
+        U: Component,
+        F: Future<Output = C> + 'static,
+        C: Component,
+    {
+        let node = Self::new(function::create_element("div"));
+        node.push_child(placeholder().into());
+        node.set_attr("style", "display: contents");
+        Dom::set_attribute(&node.node, PENDING, "");
+
+        let elem = node.clone();
+        node.add_task(task::spawn(async move {
+            let content: Self = future.await.into();
+            elem.replace_children(content.clone());
+            for x in Dom::children(&elem.node) {
+                if x != *content {
+                    Dom::remove_child(&elem.node, &x);
+                }
+            }
+            Dom::remove_attribute(&elem.node, PENDING);
+        }));
+        node
+    }
+
+    /// Create a `Node` using `func`, stopping the tasks spawned meanwhile when it is torn down,
+    /// eg: the ones binding the states to the query string.
+    pub(crate) fn scoped(func: impl FnOnce() -> Self) -> Self {
+        let mut node = None;
+        let tasks = task::scope(|| node = Some(func()));
+        let node = node.unwrap();
+        tasks.into_iter().for_each(|x| node.add_task(x));
+        node
+    }
+
+    /// Remove the `Node` from the DOM, and free the event listeners and stop the tasks
+    /// updating it and its children.
+    ///
+    /// Pages rendered using `App::render_single` are kept alive forever,
+    /// so keep a clone of the `Node` to unmount it later.
+    /// The `Node` should not be used after it is unmounted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn ui() -> Node {
+    ///     let value = StateAtomic::new(0);
+    ///     div!(h1!("Value ", value.clone())).into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     let page = ui();
+    ///     App::render_single(page.clone());
+    ///
+    ///     page.unmount();
+    /// }
+    /// ```
+    pub fn unmount(&self) {
+        if let Some(parent) = Dom::parent(&self.node) {
+            Dom::remove_child(&parent, &self.node);
+        }
+        self.teardown();
+    }
+
+    /// Free the event listeners and stop the tasks of the `Node` and its children.
+    pub(crate) fn teardown(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+
+        let callbacks = core::mem::take(&mut *self.callbacks.lock());
+        for (event, x) in callbacks.iter() {
+            Dom::remove_event_listener(&self.node, event, x);
+        }
+        // The callbacks are dropped later, as one of them might be running right now.
+        task::spawn(async move { drop(callbacks) });
+
+        for child in self.children.lock().drain(..) {
+            child.teardown();
+        }
+    }
+
+    /// Use the `node` rendered on the server instead of the one created,
+    /// moving the event listeners and the attributes to it.
+    pub(crate) fn claim(&self, node: DomNode) {
+        let created = &self.node.created;
+        for (event, x) in self.callbacks.lock().iter() {
+            Dom::remove_event_listener(created, event, x);
+            Dom::add_event_listener(&node, event, x);
+        }
+        let attributes = Dom::attributes(created);
+        for (key, value) in &attributes {
+            Dom::set_attribute(&node, key, value);
+        }
+        if Dom::is_text(&node) && Dom::text(&node) != Dom::text(created) {
+            Dom::set_text(&node, &Dom::text(created));
+        }
+        // The markup rendered on the server is shown instead of the placeholder.
+        if attributes.iter().any(|x| x.0 == PENDING) {
+            for child in self.children.lock().drain(..) {
+                child.teardown();
+            }
+        }
+        let _ = self.node.claimed.set(node);
+    }
+
+    pub(crate) fn children(&self) -> Vec<Self> {
+        self.children.lock().clone()
+    }
+
+    pub(crate) fn add_task(&self, task: Task) {
+        self.tasks.lock().push(task);
+    }
+
     pub(crate) fn push_child(&self, node: Self) {
-        self.node.append_child(node.as_ref()).unwrap();
-        self.children.lock().push(node);
+        let mut lock = self.children.lock();
+        self.append(&node, lock.len());
+        lock.push(node);
     }
 
     pub(crate) fn insert_child(&self, index: usize, node: Self) {
         let mut lock = self.children.lock();
         lock.insert(index, node);
-        self.node
-            .insert_before(lock[index].as_ref(), Some(lock[index + 1].as_ref()))
-            .unwrap();
+        let reference = lock.get(index + 1).map(|x| &**x.node);
+        Dom::insert_before(&self.node, &lock[index].node, reference);
     }
 
     pub(crate) fn pop_child(&self) {
         if let Some(node) = self.children.lock().pop() {
-            self.node.remove_child(node.as_ref()).unwrap();
+            Dom::remove_child(&self.node, &node.node);
+            node.teardown();
         }
     }
 
     pub(crate) fn remove_child(&self, index: usize) {
         let mut lock = self.children.lock();
         if lock.get(index).is_some() {
-            self.node.remove_child(lock.remove(index).as_ref()).unwrap();
+            let node = lock.remove(index);
+            Dom::remove_child(&self.node, &node.node);
+            node.teardown();
+        }
+    }
+
+    pub(crate) fn replace_child(&self, index: usize, node: Self) {
+        let mut lock = self.children.lock();
+        let old = core::mem::replace(&mut lock[index], node);
+        Dom::insert_before(&self.node, &lock[index].node, Some(&old.node));
+        Dom::remove_child(&self.node, &old.node);
+        old.teardown();
+    }
+
+    pub(crate) fn move_child(&self, from: usize, to: usize) {
+        let mut lock = self.children.lock();
+        let node = lock.remove(from);
+        lock.insert(to, node);
+        let reference = lock.get(to + 1).map(|x| &**x.node);
+        Dom::insert_before(&self.node, &lock[to].node, reference);
+    }
+
+    /// Put the children in the `order` of their old indices.
+    pub(crate) fn reorder_children(&self, order: &[usize]) {
+        let sources = order.iter().copied().map(Some).collect::<Vec<_>>();
+        self.patch_children(&sources, |_| unreachable!("every child is reused"));
+    }
+
+    /// Make the child at every index the old child at `sources[index]`,
+    /// or a new one made by `create` if there is none, and remove the old children not reused.
+    /// Only the children which are not in the longest run already in order are moved.
+    pub(crate) fn patch_children(
+        &self,
+        sources: &[Option<usize>],
+        mut create: impl FnMut(usize) -> Self,
+    ) {
+        let mut lock = self.children.lock();
+        let old = core::mem::take(&mut *lock);
+
+        let mut used = alloc::vec![false; old.len()];
+        sources.iter().flatten().for_each(|&x| used[x] = true);
+        for (node, used) in old.iter().zip(used) {
+            if !used {
+                Dom::remove_child(&self.node, &node.node);
+                node.teardown();
+            }
         }
+
+        let reused = sources.iter().flatten().copied().collect::<Vec<_>>();
+        let mut in_order = increasing(&reused).into_iter();
+        let keep = sources
+            .iter()
+            .map(|x| x.is_some() && in_order.next().unwrap_or(false))
+            .collect::<Vec<_>>();
+
+        for (i, source) in sources.iter().enumerate() {
+            let node = match source {
+                Some(x) => old[*x].clone(),
+                None => create(i),
+            };
+            lock.push(node);
+        }
+        for i in (0..lock.len()).rev() {
+            if !keep[i] {
+                let reference = lock.get(i + 1).map(|x| &**x.node);
+                Dom::insert_before(&self.node, &lock[i].node, reference);
+            }
+        }
+    }
+
+    /// Remove the children which are not marked to be kept.
+    pub(crate) fn retain_children(&self, keep: &[bool]) {
+        let mut lock = self.children.lock();
+        let mut keep = keep.iter();
+        lock.retain(|node| {
+            let keep = keep.next().copied().unwrap_or(true);
+            if !keep {
+                Dom::remove_child(&self.node, &node.node);
+                node.teardown();
+            }
+            keep
+        });
+    }
+
+    pub(crate) fn truncate_children(&self, len: usize) {
+        let mut lock = self.children.lock();
+        while lock.len() > len {
+            if let Some(node) = lock.pop() {
+                Dom::remove_child(&self.node, &node.node);
+                node.teardown();
+            }
+        }
+    }
+
+    pub(crate) fn replace_children(&self, node: Self) {
+        let mut lock = self.children.lock();
+        for child in lock.drain(..) {
+            Dom::remove_child(&self.node, &child.node);
+            child.teardown();
+        }
+        self.append(&node, 0);
+        lock.push(node);
     }
 
     pub(crate) fn set_id(&self, id: impl AsRef<str>) {
@@ -70,9 +338,7 @@ impl Node {
             }
         }
         lock.replace(id.as_ref().to_string());
-        self.node
-            .unchecked_ref::<web_sys::Element>()
-            .set_id(id.as_ref());
+        Dom::set_attribute(&self.node, "id", id.as_ref());
     }
 
     pub(crate) fn get_id(&self) -> Option<String> {
@@ -128,19 +394,13 @@ impl Node {
             }
         }
         lock.insert(attr.as_ref().to_string(), val.as_ref().to_string());
-        self.node
-            .unchecked_ref::<web_sys::Element>()
-            .set_attribute(attr.as_ref(), val.as_ref())
-            .unwrap();
+        Dom::set_attribute(&self.node, attr.as_ref(), val.as_ref());
     }
 
     pub(crate) fn remove_attr(&self, attr: impl AsRef<str>) {
         let mut lock = self.attr.lock();
         if lock.remove(attr.as_ref()).is_some() {
-            self.node
-                .unchecked_ref::<web_sys::Element>()
-                .remove_attribute(attr.as_ref())
-                .unwrap();
+            Dom::remove_attribute(&self.node, attr.as_ref());
         }
     }
 
@@ -148,22 +408,15 @@ impl Node {
         self.attr.lock().get(attr.as_ref()).cloned()
     }
 
-    pub(crate) fn add_event_listener(&self, event: impl AsRef<str>, x: Closure<dyn FnMut()>) {
+    pub(crate) fn add_event_listener(&self, event: impl AsRef<str>, x: <Dom as Backend>::Listener) {
         let x = Rc::new(x);
-        self.node
-            .add_event_listener_with_callback(event.as_ref(), x.as_ref().as_ref().unchecked_ref())
-            .unwrap();
+        Dom::add_event_listener(&self.node, event.as_ref(), &x);
         self.callbacks.lock().insert(event.as_ref().to_string(), x);
     }
 
     pub(crate) fn remove_event_listener(&self, event: impl AsRef<str>) {
         if let Some(x) = self.callbacks.lock().remove(event.as_ref()) {
-            self.node
-                .remove_event_listener_with_callback(
-                    event.as_ref(),
-                    x.as_ref().as_ref().unchecked_ref(),
-                )
-                .unwrap();
+            Dom::remove_event_listener(&self.node, event.as_ref(), &x);
         }
     }
 
@@ -171,15 +424,51 @@ impl Node {
         core::mem::forget(self);
     }
 
+    /// Append the `node` to the DOM, unless it claims the nodes rendered on the server
+    /// from the `index` while hydrating.
+    fn append(&self, node: &Self, index: usize) {
+        if hydrate::active() && self.node.claimed.get().is_some() {
+            let rendered = hydrate::rendered(&self.node);
+            if hydrate::claim(&self.node, &rendered, index, node) {
+                return;
+            }
+        }
+        Dom::insert_before(&self.node, &node.node, None);
+    }
+
     fn set_class(&self, class: String) {
-        self.node
-            .unchecked_ref::<web_sys::Element>()
-            .set_class_name(&class);
+        Dom::set_attribute(&self.node, "class", &class);
+    }
+}
+
+/// Mark the elements of the longest increasing subsequence of `order`.
+fn increasing(order: &[usize]) -> Vec<bool> {
+    // `tails[k]` is the position of the smallest end of a subsequence of length `k + 1`.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = alloc::vec![usize::MAX; order.len()];
+    for (i, x) in order.iter().enumerate() {
+        let k = tails.partition_point(|&j| order[j] < *x);
+        if k > 0 {
+            previous[i] = tails[k - 1];
+        }
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
     }
+
+    let mut keep = alloc::vec![false; order.len()];
+    let mut i = tails.last().copied().unwrap_or(usize::MAX);
+    while i != usize::MAX {
+        keep[i] = true;
+        i = previous[i];
+    }
+    keep
 }
 
 impl Deref for Node {
-    type Target = web_sys::Node;
+    type Target = DomNode;
 
     fn deref(&self) -> &Self::Target {
         &self.node
@@ -188,18 +477,23 @@ impl Deref for Node {
 
 impl DerefMut for Node {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.node
+        let slot = Rc::make_mut(&mut self.node);
+        if slot.claimed.get().is_some() {
+            slot.claimed.get_mut().unwrap()
+        } else {
+            &mut slot.created
+        }
     }
 }
 
-impl From<Node> for web_sys::Node {
+impl From<Node> for DomNode {
     fn from(x: Node) -> Self {
-        x.node
+        (**x.node).clone()
     }
 }
 
-impl AsRef<web_sys::Node> for Node {
-    fn as_ref(&self) -> &web_sys::Node {
+impl AsRef<DomNode> for Node {
+    fn as_ref(&self) -> &DomNode {
         &self.node
     }
 }
@@ -0,0 +1,408 @@
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::function;
+use crate::state::StateMutex;
+
+local! {
+    static MODE: Cell<Option<Mode>> = Cell::new(None);
+    static LOCATION: RefCell<Option<StateMutex<String>>> = RefCell::new(None);
+    static NAVIGATION: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// The path of the page rendered by the `App`.
+pub(crate) fn location() -> StateMutex<String> {
+    LOCATION.with(|x| {
+        x.borrow_mut()
+            .get_or_insert_with(|| StateMutex::new(String::new()))
+            .clone()
+    })
+}
+
+/// Keep the path passed to `App::navigate`, which is added to the history
+/// only once the `App` allows it.
+pub(crate) fn set_navigation(path: Option<String>) {
+    NAVIGATION.with(|x| *x.borrow_mut() = path);
+}
+
+/// Take the path passed to `App::navigate`, if it is not handled yet.
+pub(crate) fn take_navigation() -> Option<String> {
+    NAVIGATION.with(|x| x.borrow_mut().take())
+}
+
+/// Navigate to the `path` inside the `App` using the `mode`.
+pub(crate) fn navigate(mode: Mode, path: &str) {
+    set_navigation(Some(path.to_string()));
+    function::window()
+        .dispatch_event(&web_sys::Event::new(mode.event()).unwrap())
+        .unwrap();
+
+    // No `App` is listening, so only the URL is changed.
+    if let Some(path) = take_navigation() {
+        mode.push(&path);
+    }
+}
+
+/// An event listener on the window, which is removed when it is dropped.
+pub(crate) struct Listener {
+    event: &'static str,
+    callback: Closure<dyn FnMut()>,
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        function::window()
+            .remove_event_listener_with_callback(self.event, self.callback.as_ref().unchecked_ref())
+            .unwrap();
+    }
+}
+
+/// Call `func` when navigating back or forward, until the returned `Listener` is dropped.
+/// There is nothing to listen to outside the browser.
+pub(crate) fn on_popstate(func: impl FnMut() + 'static) -> Option<Listener> {
+    if cfg!(not(target_arch = "wasm32")) {
+        return None;
+    }
+
+    let callback = Closure::wrap(Box::new(func) as Box<dyn FnMut()>);
+    function::window()
+        .add_event_listener_with_callback("popstate", callback.as_ref().unchecked_ref())
+        .unwrap();
+    Some(Listener {
+        event: "popstate",
+        callback,
+    })
+}
+
+/// The value of a parameter in `location.search`.
+/// There are no parameters outside the browser.
+pub(crate) fn query(name: &str) -> Option<String> {
+    if cfg!(not(target_arch = "wasm32")) {
+        return None;
+    }
+
+    let search = function::location().search().unwrap_or_default();
+    web_sys::UrlSearchParams::new_with_str(&search)
+        .unwrap()
+        .get(name)
+}
+
+/// Set or remove a parameter in `location.search` without adding an entry to the history.
+pub(crate) fn set_query(name: &str, value: Option<&str>) {
+    if cfg!(not(target_arch = "wasm32")) {
+        return;
+    }
+
+    let location = function::location();
+    let params =
+        web_sys::UrlSearchParams::new_with_str(&location.search().unwrap_or_default()).unwrap();
+    match value {
+        Some(x) => params.set(name, x),
+        None => params.delete(name),
+    }
+
+    let mut url = location.pathname().unwrap_or_default();
+    let search = String::from(params.to_string());
+    if !search.is_empty() {
+        url.push('?');
+        url.push_str(&search);
+    }
+    url.push_str(&location.hash().unwrap_or_default());
+
+    let history = function::window().history().unwrap();
+    history
+        .replace_state_with_url(&history.state().unwrap_or(JsValue::NULL), "", Some(&url))
+        .unwrap();
+}
+
+/// Check whether two paths have the same segments.
+pub(crate) fn same_path(a: &str, b: &str) -> bool {
+    segments(a).eq(segments(b))
+}
+
+/// The routing mode of an `App`
+///
+/// Decides which part of the URL is matched against the routes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Routes are matched against `location.hash`, eg: `/#/about`.
+    #[default]
+    Hash,
+    /// Routes are matched against `location.pathname`, eg: `/about`.
+    /// Navigation is done using `history.pushState`.
+    History,
+}
+
+/// Restores the previous mode when dropped.
+pub(crate) struct Entered(Option<Mode>);
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        MODE.with(|x| x.set(self.0));
+    }
+}
+
+impl Mode {
+    /// The mode of the `App` the nodes are rendered in, or `Mode::Hash` outside of an `App`.
+    pub(crate) fn current() -> Self {
+        Self::context().unwrap_or_default()
+    }
+
+    /// The mode of the `App` the nodes are rendered in.
+    /// It is kept by the event listeners and the futures created for them,
+    /// so that they navigate inside the same `App`.
+    pub(crate) fn context() -> Option<Self> {
+        MODE.with(Cell::get)
+    }
+
+    /// Use the `mode` until the returned value is dropped.
+    pub(crate) fn enter(mode: Option<Self>) -> Entered {
+        Entered(MODE.with(|x| x.replace(mode)))
+    }
+
+    /// The event fired by the browser when the path changes.
+    pub(crate) fn event(self) -> &'static str {
+        match self {
+            Self::Hash => "hashchange",
+            Self::History => "popstate",
+        }
+    }
+
+    /// The path the routes are matched against.
+    pub(crate) fn path(self) -> String {
+        match self {
+            Self::Hash => function::location()
+                .hash()
+                .unwrap_or_default()
+                .trim_start_matches('#')
+                .to_string(),
+            Self::History => function::location().pathname().unwrap_or_default(),
+        }
+    }
+
+    /// Add an entry with the path to the history.
+    pub(crate) fn push(self, path: &str) {
+        match self {
+            Self::Hash => function::location().set_hash(path).unwrap(),
+            Self::History => function::window()
+                .history()
+                .unwrap()
+                .push_state_with_url(&JsValue::NULL, "", Some(path))
+                .unwrap(),
+        }
+    }
+
+    /// Replace the path without adding an entry to the history.
+    pub(crate) fn replace(self, path: &str) {
+        match self {
+            Self::Hash => {
+                let mut hash = String::from("#");
+                hash.push_str(path);
+                function::location().replace(&hash).unwrap();
+            }
+            Self::History => function::window()
+                .history()
+                .unwrap()
+                .replace_state_with_url(&JsValue::NULL, "", Some(path))
+                .unwrap(),
+        }
+    }
+}
+
+/// The result of a route guard
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// # use valerie::prelude::components::*;
+/// # use valerie::router::Guard;
+/// # use wasm_bindgen_test::*;
+/// fn admin() -> Node {
+///     h1!("Admin").into()
+/// }
+///
+/// fn login() -> Node {
+///     h1!("Login").into()
+/// }
+/// # wasm_bindgen_test_configure!(run_in_browser);
+/// # #[wasm_bindgen_test]
+/// fn run() {
+///     let logged_in = StateAtomic::new(false);
+///
+///     App::new()
+///         .push("/login", login)
+///         .push("/admin", admin)
+///         .guard("/admin", move |_| {
+///             if logged_in.value() {
+///                 Guard::Allow
+///             } else {
+///                 Guard::Redirect("/login".to_string())
+///             }
+///         })
+///         .render();
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Guard {
+    /// Allow the navigation.
+    Allow,
+    /// Deny the navigation and stay on the current page.
+    Deny,
+    /// Navigate to another path instead.
+    Redirect(String),
+}
+
+/// The parameters matched from the path
+///
+/// A route can have dynamic segments like `/users/:id` which match a single segment,
+/// and a wildcard `*` or `*name` at the end which matches the rest of the path.
+/// For the route `/users/:id/files/*path`, the path `/users/42/files/a/b.txt`
+/// has the parameters `id` as `42` and `path` as `a/b.txt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    params: Vec<(&'static str, String)>,
+}
+
+impl Params {
+    /// Get a parameter parsed into a type implementing `FromStr`.
+    /// Returns `None` if the parameter does not exist or cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// fn user(id: u32) -> Node {
+    ///     h1!("User ", id).into()
+    /// }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// fn run() {
+    ///     App::new()
+    ///         .push_params("/users/:id", |params| Some(user(params.get("id")?)))
+    ///         .render();
+    /// }
+    /// ```
+    pub fn get<T>(&self, name: impl AsRef<str>) -> Option<T>
+    where
+        T: FromStr,
+    {
+        self.get_str(name)?.parse().ok()
+    }
+
+    /// Get a parameter as it is in the path.
+    pub fn get_str(&self, name: impl AsRef<str>) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|x| x.0 == name.as_ref())
+            .map(|x| x.1.as_str())
+    }
+
+    /// Fill the dynamic segments of a route `pattern` with the parameters.
+    pub(crate) fn fill(&self, pattern: &str) -> String {
+        let mut path = String::new();
+        for segment in segments(pattern) {
+            path.push('/');
+            match segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('*'))
+            {
+                Some(name) => path.push_str(self.get_str(name).unwrap_or_default()),
+                None => path.push_str(segment),
+            }
+        }
+
+        if path.is_empty() {
+            path.push('/');
+        }
+        path
+    }
+
+    /// Match the `path` against a route `pattern`.
+    pub(crate) fn matches(pattern: &'static str, path: &str) -> Option<Self> {
+        Self::matching(pattern, path, false)
+    }
+
+    /// Match the start of the `path` against a route `pattern`.
+    pub(crate) fn matches_prefix(pattern: &'static str, path: &str) -> Option<Self> {
+        Self::matching(pattern, path, true)
+    }
+
+    fn matching(pattern: &'static str, path: &str, prefix: bool) -> Option<Self> {
+        let mut params = Vec::new();
+        let mut path = segments(path);
+
+        for segment in segments(pattern) {
+            if let Some(name) = segment.strip_prefix('*') {
+                if !name.is_empty() {
+                    let rest = path.collect::<Vec<_>>().join("/");
+                    params.push((name, decode(&rest)));
+                }
+                return Some(Self { params });
+            }
+
+            let value = path.next()?;
+            if let Some(name) = segment.strip_prefix(':') {
+                params.push((name, decode(value)));
+            } else if segment != value {
+                return None;
+            }
+        }
+
+        if prefix || path.next().is_none() {
+            Some(Self { params })
+        } else {
+            None
+        }
+    }
+}
+
+/// Check whether a route pattern has no parameters.
+pub(crate) fn is_static(pattern: &str) -> bool {
+    segments(pattern).all(|x| !x.starts_with(':') && !x.starts_with('*'))
+}
+
+/// The number of segments in a route pattern.
+pub(crate) fn depth(pattern: &str) -> usize {
+    segments(pattern).count()
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|x| !x.is_empty())
+}
+
+/// Decode the percent-encoded characters of a path segment.
+fn decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        // `from_str_radix` accepts a sign, so the digits are checked first.
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .filter(|x| x.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|x| core::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(x)) => {
+                decoded.push(x);
+                i += 3;
+            }
+            (x, _) => {
+                decoded.push(x);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).unwrap_or_else(|_| segment.to_string())
+}
@@ -0,0 +1,358 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::task::{Context, Poll};
+
+use crate::backend::{Backend, Dom, DomNode};
+use crate::component::Component;
+use crate::node::PENDING;
+use crate::task;
+
+/// Elements which cannot have children, and are written without a closing tag.
+const VOID: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Elements whose text is written as it is.
+const RAW: [&str; 2] = ["script", "style"];
+
+/// The script moving the content of a resolved `template` into its placeholder.
+const RESOLVE: &str = "<script>function valerieResolve(n){\
+var t=document.querySelector('template[data-resolved=\"'+n+'\"]'),\
+d=document.querySelector('[data-pending=\"'+n+'\"]');\
+d.replaceChildren(t.content);d.removeAttribute('data-pending');t.remove()}</script>";
+
+/// Render a component to an HTML string.
+///
+/// The text and attributes have the current values of the states used.
+/// Adjacent text nodes are separated by an empty comment `<!---->`,
+/// so that they stay separate when the HTML is parsed.
+///
+/// Works outside the browser, so the first paint of a page can be rendered on the server.
+/// The markup can then be hydrated in the browser using `App::hydrate_single`.
+///
+/// The component is torn down after it is rendered, freeing its event listeners and
+/// stopping its tasks, so it is not updated anymore.
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// # use valerie::prelude::components::*;
+/// fn ui(value: StateAtomic<usize>) -> Node {
+///     div!(h1!("Value ", value)).class("counter").into()
+/// }
+///
+/// let value = StateAtomic::new(0);
+/// let page = ui(value.clone());
+/// value.put(2);
+///
+/// assert_eq!(
+///     valerie::ssr::render_to_string(page),
+///     r#"<div class="counter"><h1>Value <!---->2</h1></div>"#
+/// );
+/// ```
+pub fn render_to_string(component: impl Component) -> String {
+    let node: crate::Node = component.into();
+    task::run();
+
+    let mut html = String::new();
+    write(&node, &mut html, false);
+
+    // The listeners hold their nodes, so the nodes are only dropped once they are freed.
+    node.teardown();
+    task::run();
+    html
+}
+
+/// Render a component to chunks of HTML, sent as soon as they are ready.
+///
+/// The first chunk has the whole component, with the placeholders of the nodes
+/// created using `Node::from_future` which are still waiting.
+/// Every other chunk has the nodes of the futures which resolved since the last chunk,
+/// in a `template` along with a script moving them into their placeholders.
+/// A slow future only holds back its own nodes.
+///
+/// The futures are run on the current thread, while waiting for the next chunk.
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// # use valerie::prelude::components::*;
+/// # fn block_on<T>(future: impl core::future::Future<Output = T>) -> T {
+/// #     let waker = core::task::Waker::from(std::sync::Arc::new(Noop));
+/// #     let mut future = Box::pin(future);
+/// #     loop {
+/// #         if let core::task::Poll::Ready(x) = future
+/// #             .as_mut()
+/// #             .poll(&mut core::task::Context::from_waker(&waker))
+/// #         {
+/// #             return x;
+/// #         }
+/// #     }
+/// # }
+/// # struct Noop;
+/// # impl std::task::Wake for Noop {
+/// #     fn wake(self: std::sync::Arc<Self>) {}
+/// # }
+/// let page = div!(Node::from_future(|| "Loading...", async { "Loaded" }));
+/// let mut chunks = valerie::ssr::render_to_stream(page);
+///
+/// block_on(async {
+///     let first = chunks.next().await.unwrap();
+///     assert!(first.contains(r#"<div style="display: contents">Loaded</div>"#));
+///     assert_eq!(chunks.next().await, None);
+/// });
+/// ```
+pub fn render_to_stream(component: impl Component) -> Chunks {
+    Chunks {
+        root: component.into(),
+        sent: false,
+        nodes: Vec::new(),
+        next: 0,
+        prefix: String::new(),
+        suffix: None,
+    }
+}
+
+/// The chunks of HTML returned by `render_to_stream`
+///
+/// The component is torn down when the `Chunks` are dropped.
+pub struct Chunks {
+    root: crate::Node,
+    sent: bool,
+    nodes: Vec<(usize, DomNode)>,
+    next: usize,
+    prefix: String,
+    suffix: Option<String>,
+}
+
+impl Chunks {
+    /// The next chunk of HTML, or `None` when the whole component is rendered.
+    pub async fn next(&mut self) -> Option<String> {
+        core::future::poll_fn(|cx| self.poll_next(cx)).await
+    }
+
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<String>> {
+        // The waker is registered first, so that no wake up is missed while running.
+        task::notify(cx.waker());
+        task::run();
+
+        if !self.sent {
+            self.sent = true;
+            let root = self.root.node.clone();
+            let mut html = core::mem::take(&mut self.prefix);
+            self.mark(&root);
+            write(&root, &mut html, false);
+            if !self.nodes.is_empty() {
+                html.push_str(RESOLVE);
+            }
+            return Poll::Ready(Some(html));
+        }
+
+        if self.nodes.is_empty() {
+            return Poll::Ready(self.suffix.take());
+        }
+
+        let (resolved, pending) = core::mem::take(&mut self.nodes)
+            .into_iter()
+            .partition::<Vec<_>, _>(|x| !is_pending(&x.1));
+        self.nodes = pending;
+        if resolved.is_empty() {
+            return Poll::Pending;
+        }
+
+        let mut html = String::new();
+        for (id, node) in resolved {
+            html.push_str(&format!("<template data-resolved=\"{}\">", id));
+            for child in Dom::children(&node) {
+                self.mark(&child);
+            }
+            write_children(&node, &mut html, false);
+            html.push_str(&format!(
+                "</template><script>valerieResolve({})</script>",
+                id
+            ));
+        }
+        Poll::Ready(Some(html))
+    }
+
+    /// Number the nodes waiting for a future inside the `node`, to send them later.
+    fn mark(&mut self, node: &DomNode) {
+        if is_pending(node) {
+            self.next += 1;
+            Dom::set_attribute(node, PENDING, &self.next.to_string());
+            self.nodes.push((self.next, node.clone()));
+        }
+        for child in Dom::children(node) {
+            self.mark(&child);
+        }
+    }
+}
+
+impl Drop for Chunks {
+    fn drop(&mut self) {
+        self.root.teardown();
+        task::run();
+    }
+}
+
+/// The HTML document the pages are rendered into
+///
+/// The document loads the JS module generated by `wasm-pack`,
+/// which is expected to be built using `--target web --out-name wasm`.
+///
+/// # Examples
+///
+/// ```
+/// use valerie::ssr::Document;
+///
+/// let html = Document::new().title("Docs").render("<h1>Hello</h1>");
+/// assert!(html.contains("<title>Docs</title>"));
+/// assert!(html.contains(r#"import init from "/wasm.js""#));
+/// assert!(html.contains("<body><h1>Hello</h1></body>"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    title: String,
+    script: String,
+}
+
+impl Document {
+    /// Create a `Document` loading `/wasm.js`.
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            script: String::from("/wasm.js"),
+        }
+    }
+
+    /// Set the title of the document.
+    pub fn title(mut self, title: impl AsRef<str>) -> Self {
+        self.title = title.as_ref().to_string();
+        self
+    }
+
+    /// Set the path of the JS module generated by `wasm-pack`.
+    pub fn script(mut self, script: impl AsRef<str>) -> Self {
+        self.script = script.as_ref().to_string();
+        self
+    }
+
+    /// Render the document with the `body`.
+    pub fn render(&self, body: &str) -> String {
+        format!("{}{}{}", self.head(), body, TAIL)
+    }
+
+    /// Render the document with the `component` as the body, in chunks.
+    /// See `render_to_stream`.
+    pub fn render_stream(&self, component: impl Component) -> Chunks {
+        let mut chunks = render_to_stream(component);
+        chunks.prefix = self.head();
+        chunks.suffix = Some(String::from(TAIL));
+        chunks
+    }
+
+    /// The document up to the start of the body.
+    fn head(&self) -> String {
+        let mut title = String::new();
+        escape(&self.title, &mut title, false);
+        // The path is inside a JS string, which is inside a `script` element.
+        let script = self
+            .script
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('<', "\\u003c");
+
+        format!(
+            r#"<!doctype html>
+<html lang="en">
+    <head>
+        <meta charset="utf-8">
+        <title>{}</title>
+        <script type="module">
+            import init from "{}"
+            init()
+        </script>
+    </head>
+    <body>"#,
+            title, script
+        )
+    }
+}
+
+/// The end of the document, after the body.
+const TAIL: &str = "</body>\n</html>\n";
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write(node: &DomNode, html: &mut String, raw: bool) {
+    let tag = match Dom::tag(node) {
+        Some(x) => x,
+        None => {
+            let text = Dom::text(node);
+            if raw {
+                html.push_str(&text);
+            } else {
+                escape(&text, html, false);
+            }
+            return;
+        }
+    };
+
+    html.push('<');
+    html.push_str(&tag);
+    for (key, value) in Dom::attributes(node) {
+        html.push(' ');
+        html.push_str(&key);
+        html.push_str("=\"");
+        escape(&value, html, true);
+        html.push('"');
+    }
+    html.push('>');
+
+    if VOID.contains(&tag.as_str()) {
+        return;
+    }
+
+    write_children(node, html, RAW.contains(&tag.as_str()));
+
+    html.push_str("</");
+    html.push_str(&tag);
+    html.push('>');
+}
+
+fn write_children(node: &DomNode, html: &mut String, raw: bool) {
+    let mut text = false;
+    for child in Dom::children(node) {
+        let is_text = Dom::tag(&child).is_none();
+        if text && is_text {
+            html.push_str("<!---->");
+        }
+        text = is_text;
+        write(&child, html, raw);
+    }
+}
+
+fn is_pending(node: &DomNode) -> bool {
+    Dom::attributes(node).iter().any(|x| x.0 == PENDING)
+}
+
+fn escape(text: &str, html: &mut String, attribute: bool) {
+    for x in text.chars() {
+        match x {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' if attribute => html.push_str("&quot;"),
+            '\'' if attribute => html.push_str("&#39;"),
+            x => html.push(x),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use futures_intrusive::channel::shared::StateSender;
+
+local! {
+    static BATCH: RefCell<Option<Vec<Message>>> = RefCell::new(None);
+}
+
+/// The last message of a state changed in a batch.
+struct Message {
+    key: usize,
+    send: Box<dyn FnOnce()>,
+}
+
+/// Sends the messages when the outermost batch ends, even if it panics.
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let messages = BATCH.with(|x| x.borrow_mut().take()).unwrap_or_default();
+        for x in messages {
+            (x.send)();
+        }
+    }
+}
+
+/// Run `func`, and notify the places using the states changed in it only at the end.
+///
+/// A state changed many times is notified once, with its last value,
+/// and the states derived from many of them never see a part of the changes.
+/// The changes of a `StateVec` are not batched, as every one of them is needed.
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// # use valerie::prelude::components::*;
+/// use valerie::backend::Memory;
+/// use valerie::state::batch;
+///
+/// let first = StateMutex::new(String::from("Ada"));
+/// let last = StateMutex::new(String::from("Lovelace"));
+/// let page: Node = p!(first.clone(), " ", last.clone()).into();
+///
+/// batch(|| {
+///     first.put(String::from("Grace"));
+///     last.put(String::from("Hopper"));
+/// });
+/// assert_eq!(Memory::text_content(&page), "Grace Hopper");
+/// ```
+pub fn batch<R>(func: impl FnOnce() -> R) -> R {
+    let outer = BATCH.with(|x| {
+        let mut batch = x.borrow_mut();
+        let outer = batch.is_none();
+        if outer {
+            *batch = Some(Vec::new());
+        }
+        outer
+    });
+
+    let _guard = if outer { Some(Guard) } else { None };
+    func()
+}
+
+/// Send the `message` to the receivers of a state, or keep it until the end of the batch.
+/// The `key` identifies the state, so that only the last message of a state is kept.
+pub(crate) fn send<C>(key: usize, tx: &StateSender<C>, message: C)
+where
+    C: Clone + 'static,
+{
+    let tx = tx.clone();
+    // The message is dropped if the channel was closed in the meantime.
+    let send = move || tx.send(message).unwrap_or(());
+
+    let send = BATCH.with(|x| match x.borrow_mut().as_mut() {
+        Some(batch) => {
+            match batch.iter_mut().find(|x| x.key == key) {
+                Some(x) => x.send = Box::new(send),
+                None => batch.push(Message {
+                    key,
+                    send: Box::new(send),
+                }),
+            }
+            None
+        }
+        None => Some(send),
+    });
+
+    if let Some(send) = send {
+        send();
+    }
+}
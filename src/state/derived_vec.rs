@@ -0,0 +1,186 @@
+use alloc::sync::Arc;
+
+use super::{StateTrait, StateVec};
+use crate::component;
+use crate::html;
+use crate::task::Task;
+
+/// Stops the task updating a derived StateVec when dropped.
+struct Stop(Task);
+
+impl Drop for Stop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Keeps a derived StateVec, and the ones it is derived from, updated.
+/// It is shared by the clones of the derived StateVec and its views.
+struct Source {
+    _stop: Arc<Stop>,
+    _parent: Option<Arc<Source>>,
+}
+
+/// A read only StateVec derived from another one
+///
+/// It is returned by `StateVec::filter`, `StateVec::sort_by_key`, `StateVec::map`,
+/// `StateVec::take` and `StateVec::watch`, and is updated by them.
+/// It is kept updated for as long as it, any of its clones or any of its views is alive.
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// # use valerie::prelude::components::*;
+/// # use wasm_bindgen_test::*;
+/// # fn ui() -> Node {
+/// let vec = StateVec::with_capacity(10);
+/// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+/// let top = vec.filter(|x| x.value() % 2 == 0).take(3);
+///
+/// top.view(ul!(), |x| li!(x))
+/// # .into()
+/// # }
+/// # wasm_bindgen_test_configure!(run_in_browser);
+/// # #[wasm_bindgen_test]
+/// # fn run() {
+/// #     App::render_single(ui());
+/// # }
+/// ```
+pub struct DerivedVec<T>
+where
+    T: Send + 'static,
+{
+    vec: StateVec<T>,
+    source: Arc<Source>,
+}
+
+impl<T> DerivedVec<T>
+where
+    T: Send + 'static,
+{
+    pub(super) fn new(vec: StateVec<T>, task: Task) -> Self {
+        Self {
+            vec,
+            source: Arc::new(Source {
+                _stop: Arc::new(Stop(task)),
+                _parent: None,
+            }),
+        }
+    }
+
+    /// Keep this StateVec updated for as long as the one `derived` from it is alive.
+    fn chain<U>(&self, derived: DerivedVec<U>) -> DerivedVec<U>
+    where
+        U: Send + 'static,
+    {
+        DerivedVec {
+            vec: derived.vec,
+            source: Arc::new(Source {
+                _stop: Arc::clone(&derived.source._stop),
+                _parent: Some(Arc::clone(&self.source)),
+            }),
+        }
+    }
+}
+
+impl<T> DerivedVec<T>
+where
+    T: StateTrait + Send,
+{
+    /// Render the derived StateVec to the DOM, like `StateVec::view`.
+    /// It is kept updated until the view is torn down.
+    pub fn view<F, U, V>(&self, enclose: crate::Tag<U>, object: F) -> crate::Tag<U>
+    where
+        F: FnOnce(T) -> V,
+        F: Clone + 'static,
+        V: component::Component + 'static,
+        U: html::elements::HtmlElement,
+    {
+        self.vec
+            .view_keeping(enclose, object, Arc::clone(&self.source))
+    }
+
+    /// A StateVec with the elements of this one, which is updated also when the `state` changes.
+    /// See `StateVec::watch`.
+    pub fn watch<U>(&self, state: &U) -> DerivedVec<T>
+    where
+        U: StateTrait + 'static,
+    {
+        self.chain(self.vec.watch(state))
+    }
+
+    /// A StateVec with only the elements for which the function returns `true`.
+    /// See `StateVec::filter`.
+    pub fn filter<F>(&self, func: F) -> DerivedVec<T>
+    where
+        F: FnMut(&T) -> bool + 'static,
+    {
+        self.chain(self.vec.filter(func))
+    }
+
+    /// A StateVec with the elements sorted by the key the function returns.
+    /// See `StateVec::sort_by_key`.
+    pub fn sort_by_key<K, F>(&self, func: F) -> DerivedVec<T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K + 'static,
+    {
+        self.chain(self.vec.sort_by_key(func))
+    }
+
+    /// A StateVec with the elements returned by the function for every element.
+    /// See `StateVec::map`.
+    pub fn map<U, F>(&self, func: F) -> DerivedVec<U>
+    where
+        U: StateTrait + Send,
+        F: FnMut(&T) -> U + 'static,
+    {
+        self.chain(self.vec.map(func))
+    }
+
+    /// A StateVec with the first `n` elements.
+    /// See `StateVec::take`.
+    pub fn take(&self, n: usize) -> DerivedVec<T> {
+        self.chain(self.vec.take(n))
+    }
+
+    /// Get an element using index.
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.vec.get(index)
+    }
+
+    /// Get the len of the derived StateVec.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Check whether the derived StateVec is empty.
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+}
+
+impl<T> Clone for DerivedVec<T>
+where
+    T: Send + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            vec: self.vec.clone(),
+            source: Arc::clone(&self.source),
+        }
+    }
+}
+
+impl<T> IntoIterator for DerivedVec<T>
+where
+    T: StateTrait + Send,
+{
+    type Item = T;
+    type IntoIter = <StateVec<T> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.clone().into_iter()
+    }
+}
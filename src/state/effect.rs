@@ -0,0 +1,112 @@
+use crate::task::{self, Task};
+
+use super::{StateGroup, StateTrait};
+
+/// A handle to a function run whenever a state changes
+///
+/// The function stops running when the `Effect` is dropped.
+/// Use `forget` to keep it running for as long as the state exists.
+#[must_use = "the effect stops running when it is dropped"]
+pub struct Effect {
+    task: Task,
+}
+
+impl Effect {
+    /// Stop running the function. Same as dropping the `Effect`.
+    pub fn stop(self) {}
+
+    /// Keep running the function even after the `Effect` is dropped.
+    pub fn forget(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for Effect {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Run `func` with the new value whenever the `state` changes.
+///
+/// The function is not run for the value the `state` has now, only for the later ones.
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// use valerie::backend::Memory;
+/// use valerie::state::effect;
+///
+/// let count = StateAtomic::new(0);
+/// let log = StateMutex::new(String::new());
+///
+/// let log_move = log.clone();
+/// let handle = effect(&count, move |x| log_move.put(format!("count is {}", x)));
+///
+/// count.put(5);
+/// Memory::flush();
+/// assert_eq!(log.value(), "count is 5");
+///
+/// drop(handle);
+/// count.put(6);
+/// Memory::flush();
+/// assert_eq!(log.value(), "count is 5");
+/// ```
+pub fn effect<U, F>(state: &U, mut func: F) -> Effect
+where
+    U: StateTrait + 'static,
+    F: FnMut(U::Value) + 'static,
+{
+    let state = state.clone();
+    let rx = state.rx();
+    let mut old = super::current(&rx);
+    let task = task::spawn(async move {
+        while let Some((new, _)) = rx.receive(old).await {
+            func(state.value());
+            old = new;
+        }
+    });
+
+    Effect { task }
+}
+
+/// Run `func` with the new values whenever any of the `states` changes.
+/// The `states` are given as a tuple, like in `StateAtomic::from_all`.
+///
+/// The states changed together are seen at once, and `func` is run only once for them.
+///
+/// # Examples
+///
+/// ```
+/// # use valerie::prelude::*;
+/// use valerie::backend::Memory;
+/// use valerie::state::effect_all;
+///
+/// let price = StateAtomic::new(2);
+/// let quantity = StateAtomic::new(3);
+/// let total = StateAtomic::new(0);
+///
+/// let total_move = total.clone();
+/// effect_all((price.clone(), quantity.clone()), move |(x, y)| total_move.put(x * y)).forget();
+///
+/// price.put(4);
+/// quantity.put(5);
+/// Memory::flush();
+/// assert_eq!(total.value(), 20);
+/// ```
+pub fn effect_all<G, F>(states: G, mut func: F) -> Effect
+where
+    G: StateGroup,
+    F: FnMut(G::Value) + 'static,
+{
+    let receivers = states.receivers();
+    let task = task::spawn(async move {
+        let mut waits = super::waits(&receivers);
+        while super::changed(&receivers, &mut waits).await {
+            func(states.value());
+        }
+    });
+
+    Effect { task }
+}
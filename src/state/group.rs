@@ -0,0 +1,72 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::future::Future;
+use core::pin::Pin;
+
+use futures_intrusive::channel::StateId;
+
+use super::StateTrait;
+
+/// A future waiting for the next change of a state.
+#[doc(hidden)]
+pub type Wait = Pin<Box<dyn Future<Output = Option<StateId>>>>;
+
+/// Makes the future waiting for the change of a state after the one with the `StateId`.
+#[doc(hidden)]
+pub type Receiver = Box<dyn Fn(StateId) -> Wait>;
+
+/// A group of state variables, which a state variable can be derived from
+///
+/// Implemented for tuples of 2 to 6 variables implementing `StateTrait`,
+/// and the value is the tuple of their values.
+pub trait StateGroup: Clone + 'static {
+    /// The values of all the states.
+    type Value;
+
+    /// Returns a copy of the values stored, read at the same time.
+    fn value(&self) -> Self::Value;
+
+    /// The receivers of all the states.
+    #[doc(hidden)]
+    fn receivers(&self) -> Vec<Receiver>;
+}
+
+/// The receiver of a state, which skips the values the state had before it was created.
+pub(crate) fn receiver<U>(state: &U) -> Receiver
+where
+    U: StateTrait + 'static,
+{
+    let rx = state.rx();
+    let start = super::current(&rx);
+    Box::new(move |id| {
+        let rx = rx.clone();
+        let id = id.max(start);
+        Box::pin(async move { rx.receive(id).await.map(|x| x.0) }) as Wait
+    })
+}
+
+macro_rules! group {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name),+> StateGroup for ($($name,)+)
+        where
+            $($name: StateTrait + 'static,)+
+        {
+            type Value = ($($name::Value,)+);
+
+            fn value(&self) -> Self::Value {
+                ($(self.$index.value(),)+)
+            }
+
+            fn receivers(&self) -> Vec<Receiver> {
+                vec![$(receiver(&self.$index)),+]
+            }
+        }
+    };
+}
+
+group!(A 0, B 1);
+group!(A 0, B 1, C 2);
+group!(A 0, B 1, C 2, D 3);
+group!(A 0, B 1, C 2, D 3, E 4);
+group!(A 0, B 1, C 2, D 3, E 4, F 5);
@@ -0,0 +1,47 @@
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use futures_intrusive::channel::shared::{unbuffered_channel, Receiver, Sender};
+use parking_lot::RwLock;
+
+/// The senders of the views listening to the changes of a collection, with their ids.
+pub(super) type Senders<C> = RwLock<Vec<(usize, Sender<C>)>>;
+
+/// The id of the next view listening to the changes of a collection.
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// Removes the sender of a view from the collection it listens to, when it is dropped.
+pub(super) struct Listener<C>
+where
+    C: Send + 'static,
+{
+    id: usize,
+    tx: Weak<Senders<C>>,
+}
+
+impl<C> Drop for Listener<C>
+where
+    C: Send + 'static,
+{
+    fn drop(&mut self) {
+        if let Some(tx) = self.tx.upgrade() {
+            tx.write().retain(|x| x.0 != self.id);
+        }
+    }
+}
+
+/// Add a sender to the `senders`, which is removed when the `Listener` is dropped.
+pub(super) fn listen<C>(senders: &Arc<Senders<C>>) -> (Listener<C>, Receiver<C>)
+where
+    C: Send + 'static,
+{
+    let id = NEXT.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = unbuffered_channel();
+    senders.write().push((id, tx));
+    let listener = Listener {
+        id,
+        tx: Arc::downgrade(senders),
+    };
+    (listener, rx)
+}
@@ -2,7 +2,7 @@
 //! State variables handles the core functionality in valerie.
 //!
 //! When a change happens, the change is propagated to all the elements using the state variables
-//! using channels. The value passed is `Channel`. Whenever the update function is called,
+//! using channels. The value passed is the value of the state, or a `Channel` sharing it. Whenever the update function is called,
 //! the State variable updates its value across all the places in the DOM.
 //!
 //! This is achieved using message passing concurrency. Each place where the state is used,
@@ -10,22 +10,41 @@
 //! When the sender sends the message receiver updates the value associated.
 
 use alloc::boxed::Box;
+use alloc::string::ToString;
 use alloc::sync::Arc;
+use alloc::vec::Vec;
 use core::fmt::Display;
+use core::str::FromStr;
+use core::task::Poll;
 
+use crossbeam::atomic::AtomicCell;
 use futures_intrusive::channel::shared::{StateReceiver, StateSender};
 use futures_intrusive::channel::StateId;
 
+pub use batch::batch;
+pub use derived_vec::DerivedVec;
+pub use effect::{effect, effect_all, Effect};
+pub use group::StateGroup;
 pub use state_atomic::StateAtomic;
 pub use state_generic::StateGeneric;
+pub use state_map::StateMap;
 pub use state_mutex::StateMutex;
 pub use state_vec::StateVec;
 
-use crate::channel::Channel;
+use crate::backend::{Backend, Dom};
 use crate::component::Component;
+use crate::router;
+use crate::task;
 
+mod batch;
+mod derived_vec;
+mod effect;
+mod group;
+mod listener;
+mod sender;
 mod state_atomic;
 mod state_generic;
+mod state_map;
 mod state_mutex;
 mod state_vec;
 
@@ -38,6 +57,8 @@ pub trait StateTrait: Clone + Component + Eq {
     type Store;
 
     /// Channel type is used to pass the message between the Sender and the Receiver.
+    /// It is the value, or a `Channel` sharing the value, and is converted to a string
+    /// only where it is shown.
     type Channel: Clone;
 
     /// Returns a copy of the original value stored.
@@ -52,13 +73,63 @@ pub trait StateTrait: Clone + Component + Eq {
     /// Stores the value on to the State variable.
     fn put(&self, value: Self::Value);
 
+    /// Stores the value only if it is not equal to the one stored,
+    /// so that nothing is updated when the value is the same.
+    /// Returns `true` if the value was stored.
+    fn put_if_changed(&self, value: Self::Value) -> bool
+    where
+        Self::Value: PartialEq,
+    {
+        if self.value() == value {
+            return false;
+        }
+
+        self.put(value);
+        true
+    }
+
     /// Gives a pointer to the value.
     fn pointer(&self) -> Arc<Self::Store>;
 
     /// Update the value across the DOM. `put()` should call this.
     fn update(&self);
+
+    /// Close the channel of the State variable.
+    ///
+    /// The places using it are not updated anymore, and the futures waiting for a change
+    /// on its receivers end. The value can still be read and stored.
+    /// A sender returned by `tx()` keeps the channel open until it is dropped.
+    ///
+    /// By default nothing is done, and the channel is closed
+    /// once the State variable and all its clones are dropped.
+    fn close(&self) {}
+
+    /// Check whether the channel of the State variable is closed.
+    ///
+    /// By default the channel is never closed while the State variable is alive.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Stores the value only if the channel is open.
+    /// Returns `false` without storing it if the channel is closed,
+    /// as no receiver can get the changes anymore.
+    ///
+    /// `true` only means that the channel is open. The State variable keeps a receiver itself,
+    /// so it does not tell whether any place is still updated with the value.
+    fn try_put(&self, value: Self::Value) -> bool {
+        if self.is_closed() {
+            return false;
+        }
+
+        self.put(value);
+        true
+    }
 }
 
+/// The function comparing the values of a state, if the values which are equal are not stored.
+pub(crate) type Equal<T> = Arc<AtomicCell<Option<fn(&T, &T) -> bool>>>;
+
 pub(crate) fn from<T, U, F>(new: T, state: &U, mut func: F) -> T
 where
     T: StateTrait + 'static,
@@ -68,11 +139,11 @@ where
     let new_move = new.clone();
     let state_value = state.clone();
     let rx = state.rx();
-    wasm_bindgen_futures::spawn_local(async move {
+    // The task is stopped with the enclosing scope, eg: when an element is removed from `map`.
+    task::spawn(async move {
         let mut old = StateId::new();
         while let Some((new, _)) = rx.receive(old).await {
             new_move.put(func(state_value.value()));
-            new_move.update();
 
             old = new;
         }
@@ -81,18 +152,125 @@ where
     new
 }
 
-pub(crate) async fn change(node: impl AsRef<web_sys::Node>, rx: StateReceiver<Channel>) {
+pub(crate) fn from_all<T, G, F>(new: T, states: G, mut func: F) -> T
+where
+    T: StateTrait + 'static,
+    G: StateGroup,
+    F: FnMut(G::Value) -> T::Value + 'static,
+{
+    let new_move = new.clone();
+    let receivers = states.receivers();
+    task::spawn(async move {
+        let mut waits = waits(&receivers);
+        while changed(&receivers, &mut waits).await {
+            new_move.put(func(states.value()));
+        }
+    });
+
+    new
+}
+
+/// The `StateId` of the last value sent on the channel,
+/// so that only the values sent later are waited for.
+fn current<C>(rx: &StateReceiver<C>) -> StateId
+where
+    C: Clone,
+{
+    rx.try_receive(StateId::new())
+        .map_or_else(StateId::new, |x| x.0)
+}
+
+/// The futures waiting for the first change of the states.
+fn waits(receivers: &[group::Receiver]) -> Vec<Option<group::Wait>> {
+    receivers.iter().map(|x| Some(x(StateId::new()))).collect()
+}
+
+/// Wait until any of the states changes, or return `false` if all of them are closed.
+/// The states which changed together are handled at once,
+/// so that the values are never read halfway through the changes.
+async fn changed(receivers: &[group::Receiver], waits: &mut [Option<group::Wait>]) -> bool {
+    core::future::poll_fn(|cx| {
+        let mut changed = false;
+        for (receiver, wait) in receivers.iter().zip(waits.iter_mut()) {
+            while let Some(x) = wait.as_mut() {
+                match x.as_mut().poll(cx) {
+                    Poll::Ready(Some(id)) => {
+                        *wait = Some(receiver(id));
+                        changed = true;
+                    }
+                    Poll::Ready(None) => *wait = None,
+                    Poll::Pending => break,
+                }
+            }
+        }
+
+        if changed {
+            Poll::Ready(true)
+        } else if waits.iter().all(Option::is_none) {
+            Poll::Ready(false)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+pub(crate) fn query<T>(new: T, name: &str, default: T::Value) -> T
+where
+    T: StateTrait + 'static,
+    T::Value: FromStr,
+{
+    let name = name.to_string();
+    let new_move = new.clone();
+    let default_string = default.to_string();
+    let rx = new.rx();
+    // The listener is removed when the future ends, as the channel is closed
+    // or the `Node` it was created for is torn down.
+    task::spawn(async move {
+        let name_move = name.clone();
+        let state = new_move.clone();
+        let _listener = router::on_popstate(move || {
+            let value = router::query(&name_move).and_then(|x| x.parse().ok());
+            let value = value.unwrap_or_else(|| default.clone());
+            if value.to_string() != state.value().to_string() {
+                state.put(value);
+            }
+        });
+
+        let mut old = StateId::new();
+        while let Some((new, _)) = rx.receive(old).await {
+            let value = new_move.value().to_string();
+            if value == default_string {
+                router::set_query(&name, None);
+            } else {
+                router::set_query(&name, Some(&value));
+            }
+
+            old = new;
+        }
+    });
+
+    new
+}
+
+pub(crate) async fn change<C>(node: crate::Node, rx: StateReceiver<C>)
+where
+    C: Clone + Display,
+{
     let mut old = StateId::new();
     while let Some((new, value)) = rx.receive(old).await {
-        node.as_ref().set_node_value(Some(&value));
+        Dom::set_text(&node, &value.to_string());
         old = new;
     }
 }
 
-pub(crate) async fn state_change(mut func: Box<dyn FnMut(&str)>, rx: StateReceiver<Channel>) {
+pub(crate) async fn state_change<C>(mut func: Box<dyn FnMut(&str)>, rx: StateReceiver<C>)
+where
+    C: Clone + Display,
+{
     let mut old = StateId::new();
     while let Some((new, value)) = rx.receive(old).await {
-        func(value.as_str());
+        func(&value.to_string());
         old = new;
     }
 }
@@ -0,0 +1,113 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::any::Any;
+use core::marker::PhantomData;
+
+use futures_intrusive::channel::shared::{state_broadcast_channel, StateReceiver, StateSender};
+use parking_lot::Mutex;
+
+/// The sender of a state variable, shared by all its clones
+///
+/// The channel is closed by dropping the sender, after which the receivers get `None`.
+///
+/// The sender is kept as `Any`, so that the states using it need no bounds
+/// on their definitions, only on the impls creating the channels.
+pub(crate) struct Sender<C> {
+    inner: Arc<Mutex<Option<Box<dyn Any + Send>>>>,
+    channel: PhantomData<fn() -> C>,
+}
+
+impl<C> Sender<C>
+where
+    C: Clone + Send + 'static,
+{
+    pub(crate) fn new(tx: StateSender<C>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Some(Box::new(tx)))),
+            channel: PhantomData,
+        }
+    }
+
+    fn sender(&self) -> Option<StateSender<C>> {
+        self.inner
+            .lock()
+            .as_ref()
+            .and_then(|x| x.downcast_ref::<StateSender<C>>())
+            .cloned()
+    }
+
+    /// A clone of the sender, or the sender of a closed channel if it is closed.
+    pub(crate) fn get(&self) -> StateSender<C> {
+        self.sender().unwrap_or_else(|| {
+            // Dropping the only receiver closes the channel.
+            let (tx, _) = state_broadcast_channel();
+            tx
+        })
+    }
+
+    /// Send the `message`, or keep it until the end of the batch.
+    /// Returns `false` if the channel is closed.
+    pub(crate) fn send(&self, message: C) -> bool {
+        let tx = match self.sender() {
+            Some(tx) => tx,
+            None => return false,
+        };
+
+        super::batch::send(Arc::as_ptr(&self.inner) as usize, &tx, message);
+        true
+    }
+}
+
+impl<C> Sender<C> {
+    pub(crate) fn close(&self) {
+        self.inner.lock().take();
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.inner.lock().is_none()
+    }
+}
+
+impl<C> Clone for Sender<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            channel: PhantomData,
+        }
+    }
+}
+
+/// The receiver of a state variable, kept as `Any` like the `Sender`
+pub(crate) struct Receiver<C> {
+    inner: Arc<dyn Any + Send + Sync>,
+    channel: PhantomData<fn() -> C>,
+}
+
+impl<C> Receiver<C>
+where
+    C: Clone + Send + 'static,
+{
+    pub(crate) fn new(rx: StateReceiver<C>) -> Self {
+        Self {
+            inner: Arc::new(rx),
+            channel: PhantomData,
+        }
+    }
+
+    /// A clone of the receiver.
+    pub(crate) fn get(&self) -> StateReceiver<C> {
+        match self.inner.downcast_ref::<StateReceiver<C>>() {
+            Some(rx) => rx.clone(),
+            None => unreachable!("the receiver is created with the type of the channel"),
+        }
+    }
+}
+
+impl<C> Clone for Receiver<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            channel: PhantomData,
+        }
+    }
+}
@@ -1,45 +1,55 @@
 use alloc::sync::Arc;
 use core::fmt;
 use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
+use core::str::FromStr;
 
 use crossbeam::atomic::AtomicCell;
 use futures_intrusive::channel::shared::{state_broadcast_channel, StateReceiver, StateSender};
 
-use crate::channel::Channel;
 use crate::component::Component;
+use crate::router;
+use crate::task;
 
-use super::StateTrait;
+use super::sender::{Receiver, Sender};
+use super::{Equal, StateTrait};
 
 /// State variable to be used with types that implement `Copy`
 ///
 /// This uses `AtomicCell` of crossbeam internally.
 pub struct StateAtomic<T> {
     value: Arc<AtomicCell<T>>,
-    tx: StateSender<Channel>,
-    rx: StateReceiver<Channel>,
+    equal: Equal<T>,
+    tx: Sender<T>,
+    rx: Receiver<T>,
 }
 
 impl<T> StateTrait for StateAtomic<T>
 where
-    T: fmt::Display + Copy,
+    T: fmt::Display + Copy + Send + 'static,
 {
     type Value = T;
     type Store = AtomicCell<T>;
-    type Channel = Channel;
+    type Channel = T;
 
     fn value(&self) -> Self::Value {
         self.value.load()
     }
 
     fn tx(&self) -> StateSender<Self::Channel> {
-        self.tx.clone()
+        self.tx.get()
     }
 
     fn rx(&self) -> StateReceiver<Self::Channel> {
-        self.rx.clone()
+        self.rx.get()
     }
 
     fn put(&self, value: Self::Value) {
+        if let Some(equal) = self.equal.load() {
+            if equal(&self.value.load(), &value) {
+                return;
+            }
+        }
+
         self.value.store(value);
         self.update();
     }
@@ -49,11 +59,22 @@ where
     }
 
     fn update(&self) {
-        while self.tx.send(self.value().into()).is_err() {}
+        self.tx.send(self.value());
+    }
+
+    fn close(&self) {
+        self.tx.close();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.tx.is_closed()
     }
 }
 
-impl<T> StateAtomic<T> {
+impl<T> StateAtomic<T>
+where
+    T: Copy + Send + 'static,
+{
     /// Make a new `StateAtomic` variable.
     ///
     /// # Examples
@@ -75,17 +96,53 @@ impl<T> StateAtomic<T> {
     /// ```
     pub fn new(value: T) -> Self {
         let (tx, rx) = state_broadcast_channel();
+        let (tx, rx) = (Sender::new(tx), Receiver::new(rx));
         Self {
             value: Arc::new(AtomicCell::new(value)),
+            equal: Arc::new(AtomicCell::new(None)),
             tx,
             rx,
         }
     }
+
+    /// Store only the values which are not equal to the one stored,
+    /// for this variable and all its clones.
+    ///
+    /// Nothing is updated when the value put is the same,
+    /// including the states derived from this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// use valerie::backend::Memory;
+    /// use valerie::state::effect;
+    ///
+    /// let text = StateMutex::new(String::new());
+    /// let length = StateAtomic::from(&text, |x| x.len()).distinct();
+    /// let count = StateAtomic::new(0);
+    ///
+    /// let count_move = count.clone();
+    /// effect(&length, move |_| count_move.put(count_move.value() + 1)).forget();
+    ///
+    /// text.put(String::from("ab"));
+    /// Memory::flush();
+    /// text.put(String::from("cd"));
+    /// Memory::flush();
+    /// assert_eq!(count.value(), 1);
+    /// ```
+    pub fn distinct(self) -> Self
+    where
+        T: PartialEq,
+    {
+        self.equal.store(Some(|x: &T, y: &T| x == y));
+        self
+    }
 }
 
 impl<T> StateAtomic<T>
 where
-    T: fmt::Display + Copy + 'static,
+    T: fmt::Display + Copy + Send + 'static,
 {
     /// Derive a `StateAtomic` variable from another variable implementing `StateTrait`.
     ///
@@ -121,17 +178,94 @@ where
 
         super::from(new, state, func)
     }
+
+    /// Derive a `StateAtomic` variable from a group of variables implementing `StateTrait`,
+    /// given as a tuple.
+    ///
+    /// The value is computed again whenever any of the variables changes.
+    /// The variables changed together are seen at once, instead of one after the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// use valerie::backend::Memory;
+    ///
+    /// let price = StateAtomic::new(2);
+    /// let quantity = StateAtomic::new(3);
+    /// let total = StateAtomic::from_all((price.clone(), quantity.clone()), |(price, quantity)| {
+    ///     price * quantity
+    /// });
+    /// assert_eq!(total.value(), 6);
+    ///
+    /// price.put(5);
+    /// quantity.put(4);
+    /// Memory::flush();
+    /// assert_eq!(total.value(), 20);
+    /// ```
+    pub fn from_all<G, F>(states: G, mut func: F) -> Self
+    where
+        G: super::StateGroup,
+        F: FnMut(G::Value) -> <Self as StateTrait>::Value + 'static,
+    {
+        let value = func(states.value());
+        let new = Self::new(value);
+
+        super::from_all(new, states, func)
+    }
+
+    /// Make a `StateAtomic` variable bound to the parameter `name` in the query string of the URL.
+    ///
+    /// The initial value is parsed from `location.search`, or is `default` if the parameter
+    /// is absent or can not be parsed. Whenever the value changes, the URL is updated using
+    /// `history.replaceState`, and when navigating back or forward the value is read again.
+    /// The parameter is removed from the URL while the value is `default`.
+    /// The binding stops when the variable is closed, or when the page of the `App`
+    /// it was created in is torn down.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let page = StateAtomic::query("page", 1usize);
+    ///
+    /// div!(
+    ///     h3!("Page ", page.clone()),
+    ///     button!("Next").on_event("click", page, |x, _| {
+    ///         *x += 1;
+    ///     })
+    /// )
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn query(name: impl AsRef<str>, default: T) -> Self
+    where
+        T: FromStr,
+    {
+        let value = router::query(name.as_ref()).and_then(|x| x.parse().ok());
+        let new = Self::new(value.unwrap_or(default));
+
+        super::query(new, name.as_ref(), default)
+    }
 }
 
-impl<T> Component for StateAtomic<T> where T: fmt::Display + Copy {}
+impl<T> Component for StateAtomic<T> where T: fmt::Display + Copy + Send + 'static {}
 
 impl<T> From<StateAtomic<T>> for crate::Node
 where
-    T: fmt::Display + Copy,
+    T: fmt::Display + Copy + Send + 'static,
 {
     fn from(x: StateAtomic<T>) -> Self {
         let elem: Self = x.value().into();
-        wasm_bindgen_futures::spawn_local(super::change(elem.clone(), x.rx()));
+        elem.add_task(task::spawn(super::change(elem.clone(), x.rx())));
 
         elem
     }
@@ -149,6 +283,7 @@ impl<T> Clone for StateAtomic<T> {
     fn clone(&self) -> Self {
         Self {
             value: Arc::clone(&self.value),
+            equal: Arc::clone(&self.equal),
             tx: self.tx.clone(),
             rx: self.rx.clone(),
         }
@@ -157,7 +292,7 @@ impl<T> Clone for StateAtomic<T> {
 
 impl<T, U> Add<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + Add<U> + AddAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + Add<U> + AddAssign<U>,
 {
     type Output = Self;
 
@@ -169,7 +304,7 @@ where
 
 impl<T, U> AddAssign<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + AddAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + AddAssign<U>,
 {
     fn add_assign(&mut self, other: U) {
         let mut value = self.value();
@@ -180,7 +315,7 @@ where
 
 impl<T, U> Div<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + Div<U> + DivAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + Div<U> + DivAssign<U>,
 {
     type Output = Self;
 
@@ -192,7 +327,7 @@ where
 
 impl<T, U> DivAssign<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + DivAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + DivAssign<U>,
 {
     fn div_assign(&mut self, other: U) {
         let mut value = self.value();
@@ -203,7 +338,7 @@ where
 
 impl<T, U> Mul<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + Mul<U> + MulAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + Mul<U> + MulAssign<U>,
 {
     type Output = Self;
 
@@ -215,7 +350,7 @@ where
 
 impl<T, U> MulAssign<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + MulAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + MulAssign<U>,
 {
     fn mul_assign(&mut self, other: U) {
         let mut value = self.value();
@@ -226,7 +361,7 @@ where
 
 impl<T, U> Rem<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + Rem<U> + RemAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + Rem<U> + RemAssign<U>,
 {
     type Output = Self;
 
@@ -238,7 +373,7 @@ where
 
 impl<T, U> RemAssign<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + RemAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + RemAssign<U>,
 {
     fn rem_assign(&mut self, other: U) {
         let mut value = self.value();
@@ -249,7 +384,7 @@ where
 
 impl<T, U> Sub<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + Sub<U> + SubAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + Sub<U> + SubAssign<U>,
 {
     type Output = Self;
 
@@ -261,7 +396,7 @@ where
 
 impl<T, U> SubAssign<U> for StateAtomic<T>
 where
-    T: fmt::Display + Copy + SubAssign<U>,
+    T: fmt::Display + Copy + Send + 'static + SubAssign<U>,
 {
     fn sub_assign(&mut self, other: U) {
         let mut value = self.value();
@@ -5,7 +5,9 @@ use futures_intrusive::channel::shared::{state_broadcast_channel, StateReceiver,
 
 use crate::channel::Channel;
 use crate::component::Component;
+use crate::task;
 
+use super::sender::{Receiver, Sender};
 use super::StateTrait;
 
 /// A generic State type
@@ -16,28 +18,28 @@ pub struct StateGeneric<T, D> {
     reader: fn(&T) -> D,
     writer: fn(&T, D),
     new: fn(D) -> T,
-    tx: StateSender<Channel>,
-    rx: StateReceiver<Channel>,
+    tx: Sender<Channel<D>>,
+    rx: Receiver<Channel<D>>,
 }
 
 impl<T, D> StateTrait for StateGeneric<T, D>
 where
-    D: fmt::Display + Clone,
+    D: fmt::Display + Clone + Send + Sync + 'static,
 {
     type Value = D;
     type Store = T;
-    type Channel = Channel;
+    type Channel = Channel<D>;
 
     fn value(&self) -> Self::Value {
         (self.reader)(&self.value)
     }
 
     fn tx(&self) -> StateSender<Self::Channel> {
-        self.tx.clone()
+        self.tx.get()
     }
 
     fn rx(&self) -> StateReceiver<Self::Channel> {
-        self.rx.clone()
+        self.rx.get()
     }
 
     fn put(&self, value: Self::Value) {
@@ -50,11 +52,22 @@ where
     }
 
     fn update(&self) {
-        while self.tx.send(self.value().into()).is_err() {}
+        self.tx.send(self.value().into());
+    }
+
+    fn close(&self) {
+        self.tx.close();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.tx.is_closed()
     }
 }
 
-impl<T, D> StateGeneric<T, D> {
+impl<T, D> StateGeneric<T, D>
+where
+    D: Send + Sync + 'static,
+{
     /// To make a new Generic State type.
     ///
     /// Description
@@ -65,6 +78,7 @@ impl<T, D> StateGeneric<T, D> {
     ///  * `new` - To make enclose the `value`, eg: Mutex::new
     pub fn new(value: D, reader: fn(&T) -> D, writer: fn(&T, D), new: fn(D) -> T) -> Self {
         let (tx, rx) = state_broadcast_channel();
+        let (tx, rx) = (Sender::new(tx), Receiver::new(rx));
         Self {
             value: Arc::new(new(value)),
             reader,
@@ -79,7 +93,7 @@ impl<T, D> StateGeneric<T, D> {
 impl<T, D> StateGeneric<T, D>
 where
     T: 'static,
-    D: fmt::Display + Clone + 'static,
+    D: fmt::Display + Clone + Send + Sync + 'static,
 {
     /// A from function to derive the value from other state variables.
     pub fn from<U, F>(
@@ -98,17 +112,35 @@ where
 
         super::from(new, state, func)
     }
+
+    /// A from function to derive the value from a group of state variables, given as a tuple.
+    pub fn from_all<G, F>(
+        states: G,
+        mut func: F,
+        reader: fn(&T) -> D,
+        writer: fn(&T, D),
+        new: fn(D) -> T,
+    ) -> Self
+    where
+        G: super::StateGroup,
+        F: FnMut(G::Value) -> <Self as StateTrait>::Value + 'static,
+    {
+        let value = func(states.value());
+        let new = Self::new(value, reader, writer, new);
+
+        super::from_all(new, states, func)
+    }
 }
 
-impl<T, D> Component for StateGeneric<T, D> where D: fmt::Display + Clone {}
+impl<T, D> Component for StateGeneric<T, D> where D: fmt::Display + Clone + Send + Sync + 'static {}
 
 impl<T, D> From<StateGeneric<T, D>> for crate::Node
 where
-    D: fmt::Display + Clone,
+    D: fmt::Display + Clone + Send + Sync + 'static,
 {
     fn from(x: StateGeneric<T, D>) -> Self {
         let elem: Self = x.value().into();
-        wasm_bindgen_futures::spawn_local(super::change(elem.clone(), x.rx()));
+        elem.add_task(task::spawn(super::change(elem.clone(), x.rx())));
 
         elem
     }
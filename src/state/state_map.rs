@@ -0,0 +1,306 @@
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
+
+use parking_lot::RwLock;
+
+use super::listener::{self, Senders};
+use super::StateTrait;
+use crate::component;
+use crate::html;
+use crate::task;
+
+#[derive(Clone)]
+enum Change<K, V>
+where
+    K: Send,
+    V: Send,
+{
+    Insert(usize, K, V),
+    Replace(usize, K, V),
+    Remove(usize),
+}
+
+/// A map of States, sorted by key
+///
+/// Any type implementing `StateTrait` can be used as the values of StateMap.
+///
+/// This uses `RwLock` of parking_lot internally with `BTreeMap` from alloc.
+pub struct StateMap<K, V>
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    value: Arc<RwLock<BTreeMap<K, V>>>,
+    tx: Arc<Senders<Change<K, V>>>,
+}
+
+impl<K, V> StateMap<K, V>
+where
+    K: Ord + Send,
+    V: Send,
+{
+    /// Declare an empty StateMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let map = StateMap::new();
+    /// map.insert(1, StateMutex::new(String::from("One")));
+    ///
+    /// map.view(ul!(), |_, x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn new() -> Self {
+        Self {
+            value: Arc::new(RwLock::new(BTreeMap::new())),
+            tx: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+impl<K, V> StateMap<K, V>
+where
+    K: Ord + Clone + Send,
+    V: StateTrait + Send,
+{
+    /// Render the StateMap to the DOM, with the elements in the order of their keys.
+    /// When an entry is inserted or removed, only the element of its key is added or removed.
+    ///
+    ///  - `enclose` is the `Component` inside which all of the elements will be present.
+    ///  - `object` is the function which will return a `Component` when passed a key and
+    ///    its value. The function will define how all the elements will be seen inside
+    ///    the `enclose`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let users = StateMap::new();
+    /// users.insert(7, StateMutex::new(String::from("Grace")));
+    /// users.insert(3, StateMutex::new(String::from("Ada")));
+    ///
+    /// users.view(ul!(), |id, name| li!(id, ": ", name))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn view<F, U, W>(&self, enclose: crate::Tag<U>, object: F) -> crate::Tag<U>
+    where
+        F: FnOnce(K, V) -> W,
+        F: Clone + 'static,
+        W: component::Component + 'static,
+        U: html::elements::HtmlElement,
+    {
+        for (k, v) in self.value.read().iter() {
+            enclose
+                .node
+                .push_child(object.clone()(k.clone(), v.clone()).into())
+        }
+
+        let node = enclose.node.clone();
+        let (listener, rx) = listener::listen(&self.tx);
+        enclose.node.add_task(task::spawn(async move {
+            // The sender is removed once the view is torn down.
+            let _listener = listener;
+            while let Some(change) = rx.receive().await {
+                match change {
+                    Change::Insert(i, k, v) => node.insert_child(i, object.clone()(k, v).into()),
+                    Change::Replace(i, k, v) => node.replace_child(i, object.clone()(k, v).into()),
+                    Change::Remove(i) => node.remove_child(i),
+                }
+            }
+        }));
+
+        enclose
+    }
+
+    /// Insert an entry into the StateMap, returning the value which was stored for the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let map = StateMap::new();
+    /// map.insert("b", StateAtomic::new(2));
+    /// map.insert("a", StateAtomic::new(1));
+    ///
+    /// map.view(ul!(), |k, v| li!(k, " = ", v))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut lock = self.value.write();
+        let index = lock.range(..&key).count();
+        let old = lock.insert(key.clone(), value.clone());
+        drop(lock);
+
+        if old.is_some() {
+            self.update(Change::Replace(index, key, value));
+        } else {
+            self.update(Change::Insert(index, key, value));
+        }
+        old
+    }
+
+    /// Remove an entry from the StateMap by key, returning its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let map = StateMap::new();
+    /// (0..10).for_each(|x| {
+    ///     map.insert(x, StateAtomic::new(x * x));
+    /// });
+    /// map.remove(&3);
+    ///
+    /// map.view(ul!(), |_, x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut lock = self.value.write();
+        let index = lock.range(..key).count();
+        let old = lock.remove(key);
+        drop(lock);
+
+        if old.is_some() {
+            self.update(Change::Remove(index));
+        }
+        old
+    }
+
+    /// Get the value of a key from the StateMap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let map = StateMap::new();
+    /// map.insert(1, StateAtomic::new(10));
+    ///
+    /// div!(
+    ///     map.get(&1).unwrap(),
+    ///     map.view(ul!(), |_, x| li!(x))
+    /// )
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.value.read().get(key).cloned()
+    }
+
+    /// Check whether the StateMap has an entry for the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.value.read().contains_key(key)
+    }
+
+    /// The keys of the StateMap, in order.
+    pub fn keys(&self) -> Vec<K> {
+        self.value.read().keys().cloned().collect()
+    }
+
+    /// Get the len of the StateMap.
+    pub fn len(&self) -> usize {
+        self.value.read().len()
+    }
+
+    /// Check whether StateMap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.value.read().is_empty()
+    }
+
+    fn update(&self, change: Change<K, V>) {
+        self.tx
+            .read()
+            .iter()
+            .map(|x| (x.1.clone(), change.clone()))
+            .for_each(|(tx, change)| {
+                task::execute(async move {
+                    tx.send(change).await.unwrap_or(());
+                });
+            });
+    }
+}
+
+impl<K, V> Default for StateMap<K, V>
+where
+    K: Ord + Send,
+    V: Send,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Clone for StateMap<K, V>
+where
+    K: Send,
+    V: Send,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: Arc::clone(&self.value),
+            tx: Arc::clone(&self.tx),
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for StateMap<K, V>
+where
+    K: Ord + Send,
+    V: StateTrait + Send,
+{
+    fn from_iter<U>(iter: U) -> Self
+    where
+        U: IntoIterator<Item = (K, V)>,
+    {
+        StateMap {
+            value: Arc::new(RwLock::new(iter.into_iter().collect())),
+            tx: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
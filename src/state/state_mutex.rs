@@ -1,46 +1,60 @@
 use alloc::sync::Arc;
 use core::fmt;
 use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
+use core::str::FromStr;
 
+use crossbeam::atomic::AtomicCell;
 use futures_intrusive::channel::shared::{state_broadcast_channel, StateReceiver, StateSender};
 use parking_lot::Mutex;
 
 use crate::channel::Channel;
 use crate::component::Component;
+use crate::router;
+use crate::task;
 
-use super::StateTrait;
+use super::sender::{Receiver, Sender};
+use super::{Equal, StateTrait};
 
 /// State variable to be used with types that implement `Clone`
 ///
 /// This uses `Mutex` of parking_lot internally.
 pub struct StateMutex<T> {
-    value: Arc<Mutex<T>>,
-    tx: StateSender<Channel>,
-    rx: StateReceiver<Channel>,
+    value: Arc<Mutex<Arc<T>>>,
+    equal: Equal<T>,
+    tx: Sender<Channel<T>>,
+    rx: Receiver<Channel<T>>,
 }
 
 impl<T> StateTrait for StateMutex<T>
 where
-    T: fmt::Display + Clone,
+    T: fmt::Display + Clone + Send + Sync + 'static,
 {
     type Value = T;
-    type Store = Mutex<T>;
-    type Channel = Channel;
+    type Store = Mutex<Arc<T>>;
+    type Channel = Channel<T>;
 
     fn value(&self) -> Self::Value {
-        self.value.lock().clone()
+        T::clone(&self.value.lock())
     }
 
     fn tx(&self) -> StateSender<Self::Channel> {
-        self.tx.clone()
+        self.tx.get()
     }
 
     fn rx(&self) -> StateReceiver<Self::Channel> {
-        self.rx.clone()
+        self.rx.get()
     }
 
     fn put(&self, value: Self::Value) {
-        *self.value.lock() = value;
+        {
+            let mut lock = self.value.lock();
+            if let Some(equal) = self.equal.load() {
+                if equal(&lock, &value) {
+                    return;
+                }
+            }
+            *lock = Arc::new(value);
+        }
         self.update();
     }
 
@@ -49,11 +63,23 @@ where
     }
 
     fn update(&self) {
-        while self.tx.send(self.value.lock().into()).is_err() {}
+        let value = Arc::clone(&self.value.lock());
+        self.tx.send(Channel::shared(value));
+    }
+
+    fn close(&self) {
+        self.tx.close();
+    }
+
+    fn is_closed(&self) -> bool {
+        self.tx.is_closed()
     }
 }
 
-impl<T> StateMutex<T> {
+impl<T> StateMutex<T>
+where
+    T: Send + Sync + 'static,
+{
     /// Make a new `StateMutex` variable.
     ///
     /// # Examples
@@ -75,17 +101,52 @@ impl<T> StateMutex<T> {
     /// ```
     pub fn new(value: T) -> Self {
         let (tx, rx) = state_broadcast_channel();
+        let (tx, rx) = (Sender::new(tx), Receiver::new(rx));
         Self {
-            value: Arc::new(Mutex::new(value)),
+            value: Arc::new(Mutex::new(Arc::new(value))),
+            equal: Arc::new(AtomicCell::new(None)),
             tx,
             rx,
         }
     }
+
+    /// Store only the values which are not equal to the one stored,
+    /// for this variable and all its clones.
+    ///
+    /// Nothing is updated when the value put is the same,
+    /// including the states derived from this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// use valerie::backend::Memory;
+    /// use valerie::state::effect;
+    ///
+    /// let name = StateMutex::new(String::from("Ada")).distinct();
+    /// let count = StateAtomic::new(0);
+    ///
+    /// let count_move = count.clone();
+    /// effect(&name, move |_| count_move.put(count_move.value() + 1)).forget();
+    ///
+    /// name.put(String::from("Ada"));
+    /// Memory::flush();
+    /// name.put(String::from("Grace"));
+    /// Memory::flush();
+    /// assert_eq!(count.value(), 1);
+    /// ```
+    pub fn distinct(self) -> Self
+    where
+        T: PartialEq,
+    {
+        self.equal.store(Some(|x: &T, y: &T| x == y));
+        self
+    }
 }
 
 impl<T> StateMutex<T>
 where
-    T: fmt::Display + Clone + 'static,
+    T: fmt::Display + Clone + Send + Sync + 'static,
 {
     /// Derive a `StateAtomic` variable from another variable implementing `StateTrait`.
     ///
@@ -125,17 +186,91 @@ where
 
         super::from(new, state, func)
     }
+
+    /// Derive a `StateMutex` variable from a group of variables implementing `StateTrait`,
+    /// given as a tuple.
+    ///
+    /// The value is computed again whenever any of the variables changes.
+    /// The variables changed together are seen at once, instead of one after the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// use valerie::backend::Memory;
+    ///
+    /// let first = StateMutex::new(String::from("Ada"));
+    /// let count = StateAtomic::new(1);
+    /// let greeting = StateMutex::from_all((first.clone(), count.clone()), |(first, count)| {
+    ///     format!("{} has {} messages", first, count)
+    /// });
+    ///
+    /// first.put(String::from("Grace"));
+    /// count.put(3);
+    /// Memory::flush();
+    /// assert_eq!(greeting.value(), "Grace has 3 messages");
+    /// ```
+    pub fn from_all<G, F>(states: G, mut func: F) -> Self
+    where
+        G: super::StateGroup,
+        F: FnMut(G::Value) -> <Self as StateTrait>::Value + 'static,
+    {
+        let value = func(states.value());
+        let new = Self::new(value);
+
+        super::from_all(new, states, func)
+    }
+
+    /// Make a `StateMutex` variable bound to the parameter `name` in the query string of the URL.
+    ///
+    /// The initial value is parsed from `location.search`, or is `default` if the parameter
+    /// is absent or can not be parsed. Whenever the value changes, the URL is updated using
+    /// `history.replaceState`, and when navigating back or forward the value is read again.
+    /// The parameter is removed from the URL while the value is `default`.
+    /// The binding stops when the variable is closed, or when the page of the `App`
+    /// it was created in is torn down.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let search = StateMutex::query("search", String::new());
+    ///
+    /// div!(
+    ///     input!("text").double_bind(search.clone()),
+    ///     h3!("Searching for ", search)
+    /// )
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn query(name: impl AsRef<str>, default: T) -> Self
+    where
+        T: FromStr,
+    {
+        let value = router::query(name.as_ref()).and_then(|x| x.parse().ok());
+        let new = Self::new(value.unwrap_or_else(|| default.clone()));
+
+        super::query(new, name.as_ref(), default)
+    }
 }
 
-impl<T> Component for StateMutex<T> where T: fmt::Display + Clone {}
+impl<T> Component for StateMutex<T> where T: fmt::Display + Clone + Send + Sync + 'static {}
 
 impl<T> From<StateMutex<T>> for crate::Node
 where
-    T: fmt::Display + Clone,
+    T: fmt::Display + Clone + Send + Sync + 'static,
 {
     fn from(x: StateMutex<T>) -> Self {
         let elem: Self = x.value.lock().into();
-        wasm_bindgen_futures::spawn_local(super::change(elem.clone(), x.rx()));
+        elem.add_task(task::spawn(super::change(elem.clone(), x.rx())));
 
         elem
     }
@@ -153,6 +288,7 @@ impl<T> Clone for StateMutex<T> {
     fn clone(&self) -> Self {
         Self {
             value: Arc::clone(&self.value),
+            equal: Arc::clone(&self.equal),
             tx: self.tx.clone(),
             rx: self.rx.clone(),
         }
@@ -161,7 +297,7 @@ impl<T> Clone for StateMutex<T> {
 
 impl<T, U> Add<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + Add<U> + AddAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + Add<U> + AddAssign<U>,
 {
     type Output = Self;
 
@@ -173,16 +309,16 @@ where
 
 impl<T, U> AddAssign<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + AddAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + AddAssign<U>,
 {
     fn add_assign(&mut self, other: U) {
-        *self.value.lock() += other;
+        *Arc::make_mut(&mut self.value.lock()) += other;
     }
 }
 
 impl<T, U> Div<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + Div<U> + DivAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + Div<U> + DivAssign<U>,
 {
     type Output = Self;
 
@@ -194,16 +330,16 @@ where
 
 impl<T, U> DivAssign<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + DivAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + DivAssign<U>,
 {
     fn div_assign(&mut self, other: U) {
-        *self.value.lock() /= other;
+        *Arc::make_mut(&mut self.value.lock()) /= other;
     }
 }
 
 impl<T, U> Mul<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + Mul<U> + MulAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + Mul<U> + MulAssign<U>,
 {
     type Output = Self;
 
@@ -215,16 +351,16 @@ where
 
 impl<T, U> MulAssign<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + MulAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + MulAssign<U>,
 {
     fn mul_assign(&mut self, other: U) {
-        *self.value.lock() *= other;
+        *Arc::make_mut(&mut self.value.lock()) *= other;
     }
 }
 
 impl<T, U> Rem<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + Rem<U> + RemAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + Rem<U> + RemAssign<U>,
 {
     type Output = Self;
 
@@ -236,16 +372,16 @@ where
 
 impl<T, U> RemAssign<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + RemAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + RemAssign<U>,
 {
     fn rem_assign(&mut self, other: U) {
-        *self.value.lock() %= other;
+        *Arc::make_mut(&mut self.value.lock()) %= other;
     }
 }
 
 impl<T, U> Sub<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + Sub<U> + SubAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + Sub<U> + SubAssign<U>,
 {
     type Output = Self;
 
@@ -257,9 +393,9 @@ where
 
 impl<T, U> SubAssign<U> for StateMutex<T>
 where
-    T: fmt::Display + Clone + SubAssign<U>,
+    T: fmt::Display + Clone + Send + Sync + 'static + SubAssign<U>,
 {
     fn sub_assign(&mut self, other: U) {
-        *self.value.lock() -= other;
+        *Arc::make_mut(&mut self.value.lock()) -= other;
     }
 }
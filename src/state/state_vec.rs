@@ -1,15 +1,23 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
 use alloc::sync::Arc;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::fmt::Display;
 use core::iter::FromIterator;
 // use core::ops::{Index, IndexMut};
 
-use futures_intrusive::channel::shared::{unbuffered_channel, Sender};
+use futures_intrusive::channel::StateId;
 use parking_lot::RwLock;
 
+use super::derived_vec::DerivedVec;
+use super::group::{self, Receiver, Wait};
+use super::listener::{self, Senders};
 use super::{StateAtomic, StateMutex, StateTrait};
 use crate::component;
 use crate::html;
+use crate::task::{self, Task};
 
 #[derive(Clone)]
 enum Change<T>
@@ -20,6 +28,13 @@ where
     Push(T),
     Remove(usize),
     Pop,
+    Set(usize, T),
+    Move(usize, usize),
+    Reorder(Vec<usize>),
+    Retain(Vec<bool>),
+    Truncate(usize),
+    Extend(Vec<T>),
+    Replace(Vec<T>, Vec<T>),
 }
 
 /// A vector of States
@@ -33,7 +48,20 @@ where
     T: Send + 'static,
 {
     value: Arc<RwLock<Vec<T>>>,
-    tx: Arc<RwLock<Vec<Sender<Change<T>>>>>,
+    tx: Arc<Senders<Change<T>>>,
+}
+
+/// An element mapped by `map`, with the tasks spawned by the function mapping it,
+/// which are stopped when the element is removed.
+struct Mapped<U> {
+    value: U,
+    tasks: Vec<Task>,
+}
+
+impl<U> Drop for Mapped<U> {
+    fn drop(&mut self) {
+        self.tasks.iter().for_each(Task::abort);
+    }
 }
 
 impl<T> StateVec<T>
@@ -104,6 +132,10 @@ where
     /// Render the StateVec to the DOM.
     /// It will update if any element changes or even if the list changes automatically.
     ///
+    /// The elements are keyed by the state variables themselves. When the list is replaced,
+    /// the DOM of the states which are still in it is kept and moved, and only
+    /// the new ones are rendered.
+    ///
     ///  - `enclose` is the `Component` inside which all of the elements will be present.
     ///  - `object` is the function which will return a `Component` when passed a type following
     /// the `StateTrait`. The function will define how all the elements will be seen inside
@@ -138,24 +170,54 @@ where
         F: Clone + 'static,
         V: component::Component + 'static,
         U: html::elements::HtmlElement,
+    {
+        self.view_keeping(enclose, object, ())
+    }
+
+    /// Render the StateVec like `view`, keeping `keep` alive until the view is torn down.
+    pub(super) fn view_keeping<F, U, V, K>(
+        &self,
+        enclose: crate::Tag<U>,
+        object: F,
+        keep: K,
+    ) -> crate::Tag<U>
+    where
+        F: FnOnce(T) -> V,
+        F: Clone + 'static,
+        V: component::Component + 'static,
+        U: html::elements::HtmlElement,
+        K: 'static,
     {
         for i in self.value.read().iter() {
             enclose.node.push_child(object.clone()(i.clone()).into())
         }
 
         let node = enclose.node.clone();
-        let (tx, rx) = unbuffered_channel();
-        self.tx.write().push(tx);
-        wasm_bindgen_futures::spawn_local(async move {
+        let (listener, rx) = listener::listen(&self.tx);
+        enclose.node.add_task(task::spawn(async move {
+            // The sender is removed once the view is torn down.
+            let _listener = listener;
+            let _keep = keep;
             while let Some(change) = rx.receive().await {
                 match change {
                     Change::Insert(i, x) => node.insert_child(i, object.clone()(x).into()),
                     Change::Push(x) => node.push_child(object.clone()(x).into()),
                     Change::Remove(i) => node.remove_child(i),
                     Change::Pop => node.pop_child(),
+                    Change::Set(i, x) => node.replace_child(i, object.clone()(x).into()),
+                    Change::Move(from, to) => node.move_child(from, to),
+                    Change::Reorder(order) => node.reorder_children(&order),
+                    Change::Retain(keep) => node.retain_children(&keep),
+                    Change::Truncate(len) => node.truncate_children(len),
+                    Change::Extend(x) => x
+                        .into_iter()
+                        .for_each(|x| node.push_child(object.clone()(x).into())),
+                    Change::Replace(old, new) => node.patch_children(&sources(&old, &new), |i| {
+                        object.clone()(new[i].clone()).into()
+                    }),
                 }
             }
-        });
+        }));
 
         enclose
     }
@@ -296,6 +358,576 @@ where
         self.update(Change::Pop);
     }
 
+    /// Replace the element at the index with another one.
+    /// Only the element at the index is rendered again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.set(3, StateAtomic::new(30));
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn set(&self, index: usize, value: T) {
+        self.value.write()[index] = value.clone();
+        self.update(Change::Set(index, value));
+    }
+
+    /// Swap two elements of the StateVec.
+    /// The elements are moved in the DOM, instead of being rendered again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.swap(0, 9);
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn swap(&self, a: usize, b: usize) {
+        let mut order = (0..self.len()).collect::<Vec<_>>();
+        order.swap(a, b);
+        self.value.write().swap(a, b);
+        self.update(Change::Reorder(order));
+    }
+
+    /// Move an element from one index to another, shifting the elements in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.move_item(9, 0);
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn move_item(&self, from: usize, to: usize) {
+        {
+            let mut lock = self.value.write();
+            let value = lock.remove(from);
+            lock.insert(to, value);
+        }
+        self.update(Change::Move(from, to));
+    }
+
+    /// Remove all the elements from the StateVec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.clear();
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn clear(&self) {
+        self.truncate(0);
+    }
+
+    /// Push all the elements of an iterator on to the StateVec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::new();
+    /// vec.extend((0..10).map(StateAtomic::new));
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn extend<I>(&self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let values = iter.into_iter().collect::<Vec<_>>();
+        self.value.write().extend(values.iter().cloned());
+        self.update(Change::Extend(values));
+    }
+
+    /// Keep only the first `len` elements of the StateVec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.truncate(5);
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn truncate(&self, len: usize) {
+        self.value.write().truncate(len);
+        self.update(Change::Truncate(len));
+    }
+
+    /// Keep only the elements for which the function returns `true`.
+    /// The elements removed are the only ones changed in the DOM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.retain(|x| x.value() % 2 == 0);
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn retain<F>(&self, mut func: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let keep = {
+            let mut lock = self.value.write();
+            let keep = lock.iter().map(&mut func).collect::<Vec<_>>();
+            let mut iter = keep.iter();
+            lock.retain(|_| iter.next().copied().unwrap_or(true));
+            keep
+        };
+        self.update(Change::Retain(keep));
+    }
+
+    /// Sort the StateVec using the compare function. The sort is stable.
+    /// The elements are moved in the DOM, and as few of them as possible are moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.sort_by(|a, b| b.value().cmp(&a.value()));
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn sort_by<F>(&self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let order = {
+            let mut lock = self.value.write();
+            let mut order = (0..lock.len()).collect::<Vec<_>>();
+            order.sort_by(|a, b| compare(&lock[*a], &lock[*b]));
+            *lock = order.iter().map(|i| lock[*i].clone()).collect();
+            order
+        };
+        self.update(Change::Reorder(order));
+    }
+
+    /// Reverse the order of the elements in the StateVec.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// vec.reverse();
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn reverse(&self) {
+        let order = (0..self.len()).rev().collect::<Vec<_>>();
+        self.value.write().reverse();
+        self.update(Change::Reorder(order));
+    }
+
+    /// Replace all the elements of the StateVec.
+    ///
+    /// The states which were in the StateVec before keep their DOM, which is moved
+    /// to their new places, and only the new states are rendered.
+    /// Use `replace_all_by_key` when the new elements are new states of the same items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    ///
+    /// let mut values = vec.clone().into_iter().step_by(2).collect::<Vec<_>>();
+    /// values.push(StateAtomic::new(10));
+    /// vec.replace_all(values);
+    ///
+    /// vec.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn replace_all(&self, values: Vec<T>) {
+        let old = core::mem::replace(&mut *self.value.write(), values.clone());
+        self.update(Change::Replace(old, values));
+    }
+
+    /// Replace all the elements of the StateVec, matching them to the old ones by a key.
+    ///
+    /// `key` gives the key of the value of an element, like the id of a row from a server.
+    /// A new element with the key of an old one is not added itself. Its value is put in the old
+    /// state instead, so that the DOM of the old state, with its focus and the states bound
+    /// to it, is kept. Only the elements with new keys are rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let rows = StateVec::new();
+    /// rows.push_mutex(String::from("1: Ada"));
+    /// rows.push_mutex(String::from("2: Grace"));
+    ///
+    /// let fetched = vec!["2: Grace Hopper", "3: Alan"];
+    /// rows.replace_all_by_key(
+    ///     fetched.into_iter().map(|x| StateMutex::new(String::from(x))).collect(),
+    ///     |x| x.split(':').next().unwrap_or_default().to_string(),
+    /// );
+    ///
+    /// rows.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn replace_all_by_key<K, F>(&self, values: Vec<T>, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(&T::Value) -> K,
+    {
+        let mut old = BTreeMap::new();
+        for x in self.value.read().iter() {
+            old.entry(key(&x.value())).or_insert_with(|| x.clone());
+        }
+
+        let values = values
+            .into_iter()
+            .map(|x| {
+                let value = x.value();
+                match old.remove(&key(&value)) {
+                    Some(old) => {
+                        old.put(value);
+                        old
+                    }
+                    None => x,
+                }
+            })
+            .collect();
+        self.replace_all(values);
+    }
+
+    /// A StateVec with the elements of this one, which is updated also when the `state` changes.
+    ///
+    /// The views derived from it, like `filter`, are evaluated again when the `state` changes,
+    /// so that they can depend on its value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let names = StateVec::new();
+    /// names.push_mutex(String::from("Ada"));
+    /// names.push_mutex(String::from("Grace"));
+    ///
+    /// let search = StateMutex::new(String::new());
+    /// let search_move = search.clone();
+    /// let found = names
+    ///     .watch(&search)
+    ///     .filter(move |x| x.value().contains(search_move.value().as_str()));
+    ///
+    /// div!(
+    ///     input!("text").bind(search),
+    ///     found.view(ul!(), |x| li!(x))
+    /// )
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn watch<U>(&self, state: &U) -> DerivedVec<T>
+    where
+        U: StateTrait + 'static,
+    {
+        self.derive(vec![group::receiver(state)], false, |x| x.to_vec())
+    }
+
+    /// A StateVec with only the elements for which the function returns `true`.
+    ///
+    /// It is read only, and is updated whenever this StateVec or the value of any of its elements
+    /// changes. Only the elements which come in or go out are changed in its views.
+    /// Use `watch` to evaluate it again when another state the function uses changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// let even = vec.filter(|x| x.value() % 2 == 0);
+    ///
+    /// even.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn filter<F>(&self, mut func: F) -> DerivedVec<T>
+    where
+        F: FnMut(&T) -> bool + 'static,
+    {
+        self.derive(Vec::new(), true, move |x| {
+            x.iter().filter(|x| func(x)).cloned().collect()
+        })
+    }
+
+    /// A StateVec with the elements sorted by the key the function returns. The sort is stable.
+    ///
+    /// Unlike `sort_by`, this StateVec is not changed. The StateVec returned is read only,
+    /// and is sorted again whenever this StateVec or the value of any of its elements changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// let sorted = vec.sort_by_key(|x| core::cmp::Reverse(x.value()));
+    ///
+    /// sorted.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn sort_by_key<K, F>(&self, mut func: F) -> DerivedVec<T>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K + 'static,
+    {
+        self.derive(Vec::new(), true, move |x| {
+            let mut x = x.to_vec();
+            x.sort_by_key(&mut func);
+            x
+        })
+    }
+
+    /// A StateVec with the elements returned by the function for every element.
+    ///
+    /// It is read only, and is updated whenever this StateVec changes.
+    /// The function is run once for every element, and its result is kept for as long as
+    /// the element is in this StateVec, so a state derived from the element stays updated.
+    /// The tasks it spawns, like the ones updating a derived state, are stopped
+    /// once the element is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// let squares = vec.map(|x| StateAtomic::from(x, |x| x * x));
+    ///
+    /// squares.view(ul!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn map<U, F>(&self, mut func: F) -> DerivedVec<U>
+    where
+        U: StateTrait + Send,
+        F: FnMut(&T) -> U + 'static,
+    {
+        let mut mapped = BTreeMap::<usize, Mapped<U>>::new();
+        self.derive(Vec::new(), false, move |x| {
+            // The elements which are not kept are dropped at the end, stopping their tasks.
+            let mut old = core::mem::take(&mut mapped);
+            x.iter()
+                .map(|x| {
+                    let key = address(x);
+                    if let Some(x) = mapped.get(&key) {
+                        return x.value.clone();
+                    }
+
+                    let entry = old.remove(&key).unwrap_or_else(|| {
+                        let mut value = None;
+                        let tasks = task::scope(|| value = Some(func(x)));
+                        Mapped {
+                            value: value.unwrap(),
+                            tasks,
+                        }
+                    });
+                    let value = entry.value.clone();
+                    mapped.insert(key, entry);
+                    value
+                })
+                .collect()
+        })
+    }
+
+    /// A StateVec with the first `n` elements.
+    ///
+    /// It is read only, and is updated whenever this StateVec changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// let vec = StateVec::with_capacity(10);
+    /// (0..10).for_each(|x| vec.push(StateAtomic::new(x)));
+    /// let top = vec.sort_by_key(|x| core::cmp::Reverse(x.value())).take(3);
+    ///
+    /// top.view(ol!(), |x| li!(x))
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn take(&self, n: usize) -> DerivedVec<T> {
+        self.derive(Vec::new(), false, move |x| {
+            x.iter().take(n).cloned().collect()
+        })
+    }
+
     /// Get an element from the StateVec using index.
     ///
     /// # Examples
@@ -380,22 +1012,118 @@ where
         self.value.read().is_empty()
     }
 
+    /// A StateVec with the elements returned by `func`, which is run again
+    /// whenever this StateVec or any of the states of the `receivers` change,
+    /// and if `track` is set, whenever the value of any of the elements changes.
+    ///
+    /// The task running it is stopped once the derived StateVec and its views are dropped.
+    fn derive<U, F>(&self, mut receivers: Vec<Receiver>, track: bool, mut func: F) -> DerivedVec<U>
+    where
+        U: StateTrait + Send,
+        F: FnMut(&[T]) -> Vec<U> + 'static,
+    {
+        let derived = StateVec::new();
+        *derived.value.write() = func(&self.value.read().clone());
+
+        let (listener, rx) = listener::listen(&self.tx);
+        receivers.push(Box::new(move |_| {
+            let rx = rx.clone();
+            Box::pin(async move { rx.receive().await.map(|_| StateId::new()) }) as Wait
+        }));
+
+        let source = Arc::clone(&self.value);
+        let derived_move = derived.clone();
+        let task = task::spawn(async move {
+            let _listener = listener;
+            let fixed = receivers.len();
+            let mut waits = super::waits(&receivers);
+            let mut tracked = Vec::new();
+            loop {
+                if track {
+                    self::track(
+                        &source.read(),
+                        fixed,
+                        &mut receivers,
+                        &mut waits,
+                        &mut tracked,
+                    );
+                }
+                if !super::changed(&receivers, &mut waits).await {
+                    break;
+                }
+
+                let values = source.read().clone();
+                derived_move.replace_all(func(&values));
+            }
+        });
+
+        DerivedVec::new(derived, task)
+    }
+
     fn update(&self, change: Change<T>) {
         self.tx
             .read()
             .iter()
-            .map(|x| (x.clone(), change.clone()))
+            .map(|x| (x.1.clone(), change.clone()))
             .for_each(|(tx, change)| {
-                wasm_bindgen_futures::spawn_local(async move {
+                task::execute(async move {
                     tx.send(change).await.unwrap_or(());
                 });
             });
     }
 }
 
+/// The address of the value of a state, which is the same for all its clones.
+fn address<T>(state: &T) -> usize
+where
+    T: StateTrait,
+{
+    Arc::as_ptr(&state.pointer()) as usize
+}
+
+/// Wait for the changes of the `values` after the `fixed` receivers,
+/// keeping the futures waiting for the elements which were already `tracked`.
+fn track<T>(
+    values: &[T],
+    fixed: usize,
+    receivers: &mut Vec<Receiver>,
+    waits: &mut Vec<Option<Wait>>,
+    tracked: &mut Vec<usize>,
+) where
+    T: StateTrait + 'static,
+{
+    let mut old: BTreeMap<usize, Option<Wait>> =
+        tracked.drain(..).zip(waits.drain(fixed..)).collect();
+    receivers.truncate(fixed);
+    for x in values {
+        let receiver = group::receiver(x);
+        let wait = old
+            .remove(&address(x))
+            .unwrap_or_else(|| Some(receiver(StateId::new())));
+        receivers.push(receiver);
+        waits.push(wait);
+        tracked.push(address(x));
+    }
+}
+
+/// The index of the old element for every new element, matching the same states.
+fn sources<T>(old: &[T], new: &[T]) -> Vec<Option<usize>>
+where
+    T: StateTrait,
+{
+    let mut indices = BTreeMap::<usize, Vec<usize>>::new();
+    for (i, x) in old.iter().enumerate().rev() {
+        indices.entry(address(x)).or_default().push(i);
+    }
+
+    new.iter()
+        .map(|x| indices.get_mut(&address(x)).and_then(Vec::pop))
+        .collect()
+}
+
 impl<T> StateVec<StateAtomic<T>>
 where
-    T: Copy + Send + Display,
+    T: Copy + Send + Display + 'static,
 {
     /// Push an element on the StateVec.
     /// Same as `push(StateAtomic::new(value))`.
@@ -454,7 +1182,7 @@ where
 
 impl<T> StateVec<StateMutex<T>>
 where
-    T: Clone + Send + Display,
+    T: Clone + Send + Sync + Display + 'static,
 {
     /// Push an element on the StateVec.
     /// Same as `push(StateMutex::new(value))`.
@@ -566,7 +1294,7 @@ where
 
 impl<T> FromIterator<T> for StateVec<StateAtomic<T>>
 where
-    T: Copy + Send + Display,
+    T: Copy + Send + Display + 'static,
 {
     fn from_iter<U>(iter: U) -> Self
     where
@@ -583,7 +1311,7 @@ where
 
 impl<T> FromIterator<T> for StateVec<StateMutex<T>>
 where
-    T: Clone + Send + Display,
+    T: Clone + Send + Sync + Display + 'static,
 {
     fn from_iter<U>(iter: U) -> Self
     where
@@ -1,18 +1,19 @@
 use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::fmt;
 use core::marker::PhantomData;
-use core::ops::Deref;
 use core::str::FromStr;
 
 use futures_intrusive::channel::StateId;
-use wasm_bindgen::prelude::*;
-use wasm_bindgen::JsCast;
 
+use crate::backend::{Backend, Dom, EventTrait};
 use crate::component;
 use crate::function;
 use crate::html;
+use crate::router::{self, Mode};
 use crate::state::StateTrait;
+use crate::task;
 use crate::value::Value;
 
 /// An HTML Tag
@@ -49,7 +50,7 @@ where
     pub fn new() -> Self {
         Self {
             elem_type: PhantomData,
-            node: crate::Node::new(function::create_element(T::tag()).unchecked_into()),
+            node: crate::Node::new(function::create_element(T::tag())),
         }
     }
 }
@@ -172,12 +173,13 @@ where
         F: FnMut(&mut U, &mut Self) + 'static,
     {
         let mut tag = self.clone();
-        let callback = Box::new(move || {
+        let mode = Mode::context();
+        let callback = Box::new(move |_| {
+            let _mode = Mode::enter(mode);
             func(&mut var, &mut tag);
-        }) as Box<dyn FnMut()>;
-        let x = Closure::wrap(callback);
+        }) as Box<dyn FnMut(<Dom as Backend>::Event)>;
 
-        self.node.add_event_listener(event, x);
+        self.node.add_event_listener(event, Dom::listener(callback));
 
         self
     }
@@ -396,7 +398,8 @@ where
     pub fn attr(self, key: impl AsRef<str>, value: impl Value) -> Self {
         let key = key.as_ref().to_string();
         let this = self.clone();
-        value.bind_func(Box::new(move |x| this.node.set_attr(&key, x)));
+        let tasks = task::scope(|| value.bind_func(Box::new(move |x| this.node.set_attr(&key, x))));
+        tasks.into_iter().for_each(|x| self.node.add_task(x));
         self
     }
 }
@@ -492,14 +495,7 @@ impl Tag<html::elements::Input> {
         T::Value: FromStr + Default,
     {
         self.on_event("input", var, |x, elem| {
-            x.put(
-                elem.node
-                    .node
-                    .unchecked_ref::<web_sys::HtmlDataElement>()
-                    .value()
-                    .parse()
-                    .unwrap_or_default(),
-            );
+            x.put(Dom::value(&elem.node.node).parse().unwrap_or_default());
         })
     }
 
@@ -532,19 +528,18 @@ impl Tag<html::elements::Input> {
     where
         T: StateTrait + 'static,
         T::Value: FromStr + Default,
-        T::Channel: Deref<Target = String>,
+        T::Channel: fmt::Display,
     {
         let elem = self.node.clone();
         let rx = var.rx();
 
-        wasm_bindgen_futures::spawn_local(async move {
+        self.node.add_task(task::spawn(async move {
             let mut old = StateId::new();
             while let Some((new, value)) = rx.receive(old).await {
-                elem.unchecked_ref::<web_sys::HtmlDataElement>()
-                    .set_value(&value);
+                Dom::set_value(&elem.node, &value.to_string());
                 old = new;
             }
-        });
+        }));
 
         self.bind(var)
     }
@@ -580,16 +575,81 @@ impl Tag<html::elements::Input> {
         F: 'static + Copy,
     {
         self.on_event("input", var, move |x, elem| {
-            x.put(func(
-                elem.node
-                    .node
-                    .unchecked_ref::<web_sys::HtmlDataElement>()
-                    .value(),
-            ));
+            x.put(func(Dom::value(&elem.node.node)));
         })
     }
 }
 
+impl Tag<html::elements::A> {
+    /// Link the anchor element to a path of the `App`.
+    ///
+    /// Left clicks navigate inside the `App` using `App::navigate`, while clicks with a
+    /// modifier key or another mouse button are left to the browser, eg: to open a new tab.
+    /// The class `active` is added to the element while the path is the current location.
+    /// The link uses the `Mode` of the `App` it is rendered in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use valerie::prelude::*;
+    /// # use valerie::prelude::components::*;
+    /// # use wasm_bindgen_test::*;
+    /// # fn ui() -> Node {
+    /// div!(
+    ///     Tag::<html::elements::A>::new().link("/").push("Home"),
+    ///     link!("/about", "About")
+    /// )
+    /// # .into()
+    /// # }
+    /// # wasm_bindgen_test_configure!(run_in_browser);
+    /// # #[wasm_bindgen_test]
+    /// # fn run() {
+    /// #     App::render_single(ui());
+    /// # }
+    /// ```
+    pub fn link(self, path: impl AsRef<str>) -> Self {
+        let path = path.as_ref().to_string();
+        let mode = Mode::current();
+        let tag = match mode {
+            Mode::Hash => self.href(String::from("#") + &path),
+            Mode::History => self.href(path.clone()),
+        };
+
+        let path_move = path.clone();
+        let callback = Box::new(move |event: <Dom as Backend>::Event| {
+            if event.default_prevented() || event.modified() {
+                return;
+            }
+
+            event.prevent_default();
+            router::navigate(mode, &path_move);
+        }) as Box<dyn FnMut(<Dom as Backend>::Event)>;
+        tag.node
+            .add_event_listener("click", Dom::listener(callback));
+
+        let location = router::location();
+        if router::same_path(&path, &location.value()) {
+            tag.node.insert_class("active");
+        }
+
+        let elem = tag.node.clone();
+        let rx = location.rx();
+        tag.node.add_task(task::spawn(async move {
+            let mut old = StateId::new();
+            while let Some((new, value)) = rx.receive(old).await {
+                if router::same_path(&path, &value) {
+                    elem.insert_class("active");
+                } else {
+                    elem.remove_class("active");
+                }
+                old = new;
+            }
+        }));
+
+        tag
+    }
+}
+
 impl<T> Clone for Tag<T> {
     fn clone(&self) -> Self {
         Self {
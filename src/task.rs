@@ -0,0 +1,202 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use parking_lot::Mutex;
+
+use crate::router::Mode;
+
+local! {
+    static SCOPES: RefCell<Vec<Vec<Task>>> = RefCell::new(Vec::new());
+}
+
+/// A handle to a spawned future, which can be used to stop it.
+#[derive(Clone, Default)]
+pub(crate) struct Task {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    aborted: bool,
+    waker: Option<Waker>,
+}
+
+impl Task {
+    /// Stop the future. It is dropped the next time it is polled.
+    pub(crate) fn abort(&self) {
+        let mut state = self.state.lock();
+        state.aborted = true;
+        if let Some(x) = state.waker.take() {
+            x.wake();
+        }
+    }
+}
+
+struct Abortable {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    task: Task,
+}
+
+impl Future for Abortable {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        {
+            let mut state = self.task.state.lock();
+            if state.aborted {
+                return Poll::Ready(());
+            }
+            state.waker = Some(cx.waker().clone());
+        }
+
+        self.future.as_mut().poll(cx)
+    }
+}
+
+/// Spawn a future which can be stopped using the returned `Task`.
+/// The `Task` is also collected by the enclosing `scope`, if any.
+pub(crate) fn spawn(future: impl Future<Output = ()> + 'static) -> Task {
+    let task = Task::default();
+    SCOPES.with(|x| {
+        if let Some(x) = x.borrow_mut().last_mut() {
+            x.push(task.clone());
+        }
+    });
+
+    execute(Abortable {
+        future: Box::pin(future),
+        task: task.clone(),
+    });
+    task
+}
+
+/// Spawn a future on the current thread.
+///
+/// In the browser the futures are run by the event loop,
+/// everywhere else they are run when `run` is called.
+pub fn execute<F>(future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    // The future navigates inside the `App` it was spawned in.
+    let mode = Mode::context();
+    let mut future = Box::pin(future);
+    let future = core::future::poll_fn(move |cx| {
+        let _mode = Mode::enter(mode);
+        future.as_mut().poll(cx)
+    });
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(future);
+    #[cfg(not(target_arch = "wasm32"))]
+    executor::spawn(Box::pin(future));
+}
+
+/// Run the spawned futures until all of them are waiting.
+/// Does nothing in the browser, as the futures are run by the event loop.
+pub(crate) fn run() {
+    #[cfg(not(target_arch = "wasm32"))]
+    executor::run();
+}
+
+/// Wake the `waker` the next time one of the spawned futures is woken,
+/// so that a future calling `run` can wait for them.
+/// Every future waiting this way is woken, eg: all the streams rendered at the same time.
+#[allow(unused_variables)]
+pub(crate) fn notify(waker: &Waker) {
+    #[cfg(not(target_arch = "wasm32"))]
+    executor::notify(waker);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod executor {
+    use alloc::boxed::Box;
+    use alloc::collections::BTreeMap;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+    use core::cell::{Cell, RefCell};
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll, Waker};
+
+    use parking_lot::Mutex;
+
+    type Futures = BTreeMap<usize, Pin<Box<dyn Future<Output = ()>>>>;
+
+    std::thread_local! {
+        static FUTURES: RefCell<Futures> = RefCell::new(BTreeMap::new());
+        static NEXT: Cell<usize> = const { Cell::new(0) };
+        static READY: Arc<Ready> = Arc::new(Ready::default());
+    }
+
+    /// The ids of the futures which were woken, and the wakers to notify when they are.
+    #[derive(Default)]
+    struct Ready {
+        ids: Mutex<Vec<usize>>,
+        wakers: Mutex<Vec<Waker>>,
+    }
+
+    struct Wake {
+        id: usize,
+        ready: Arc<Ready>,
+    }
+
+    impl std::task::Wake for Wake {
+        fn wake(self: Arc<Self>) {
+            self.ready.ids.lock().push(self.id);
+            let wakers = core::mem::take(&mut *self.ready.wakers.lock());
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+
+    pub(super) fn spawn(future: Pin<Box<dyn Future<Output = ()>>>) {
+        let id = NEXT.with(|x| x.replace(x.get() + 1));
+        FUTURES.with(|x| x.borrow_mut().insert(id, future));
+        READY.with(|x| x.ids.lock().push(id));
+    }
+
+    pub(super) fn notify(waker: &Waker) {
+        READY.with(|x| {
+            let mut wakers = x.wakers.lock();
+            if !wakers.iter().any(|x| x.will_wake(waker)) {
+                wakers.push(waker.clone());
+            }
+        });
+    }
+
+    pub(super) fn run() {
+        let ready = READY.with(Arc::clone);
+        loop {
+            let ids = core::mem::take(&mut *ready.ids.lock());
+            if ids.is_empty() {
+                break;
+            }
+
+            for id in ids {
+                // The future is taken out while polling, as it might spawn other futures.
+                let future = FUTURES.with(|x| x.borrow_mut().remove(&id));
+                if let Some(mut future) = future {
+                    let waker = Waker::from(Arc::new(Wake {
+                        id,
+                        ready: Arc::clone(&ready),
+                    }));
+                    if future.as_mut().poll(&mut Context::from_waker(&waker)) == Poll::Pending {
+                        FUTURES.with(|x| x.borrow_mut().insert(id, future));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Collect the `Task`s spawned while running `func`.
+pub(crate) fn scope(func: impl FnOnce()) -> Vec<Task> {
+    SCOPES.with(|x| x.borrow_mut().push(Vec::new()));
+    func();
+    SCOPES.with(|x| x.borrow_mut().pop().unwrap_or_default())
+}
@@ -3,10 +3,12 @@ use alloc::string::ToString;
 use core::fmt;
 
 use crate::state::{self, StateAtomic, StateGeneric, StateMutex, StateTrait};
+use crate::task;
 
 /// `Value` trait
 ///
 /// Implement this so that the type can be used to bind themselves to attributes.
+/// Futures spawned by `bind_func` using `execute` are not stopped when the `Tag` is unmounted.
 pub trait Value {
     /// The `bind_func` has to call the `func` function with the parameter
     /// as its own value whenever it is updated.
@@ -25,30 +27,30 @@ where
 
 impl<T> Value for StateAtomic<T>
 where
-    T: Copy + fmt::Display,
+    T: Copy + fmt::Display + Send + 'static,
 {
     fn bind_func(&self, mut func: Box<dyn FnMut(&str)>) {
         func(&self.value().to_string());
-        wasm_bindgen_futures::spawn_local(state::state_change(func, self.rx()));
+        task::spawn(state::state_change(func, self.rx()));
     }
 }
 
 impl<T> Value for StateMutex<T>
 where
-    T: Clone + fmt::Display,
+    T: Clone + fmt::Display + Send + Sync + 'static,
 {
     fn bind_func(&self, mut func: Box<dyn FnMut(&str)>) {
         func(&self.value().to_string());
-        wasm_bindgen_futures::spawn_local(state::state_change(func, self.rx()));
+        task::spawn(state::state_change(func, self.rx()));
     }
 }
 
 impl<T, D> Value for StateGeneric<T, D>
 where
-    D: Clone + fmt::Display,
+    D: Clone + fmt::Display + Send + Sync + 'static,
 {
     fn bind_func(&self, mut func: Box<dyn FnMut(&str)>) {
         func(&self.value().to_string());
-        wasm_bindgen_futures::spawn_local(state::state_change(func, self.rx()));
+        task::spawn(state::state_change(func, self.rx()));
     }
 }
@@ -0,0 +1,327 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use valerie::backend::{Backend, Memory, MemoryEvent};
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::ssr::render_to_string;
+
+#[test]
+fn counter() {
+    let value = StateAtomic::new(0isize);
+    let add: Node = button!("Add 1")
+        .on_event("click", value.clone(), |x, _| *x += 1)
+        .into();
+    let subtract: Node = button!("Subtract 1")
+        .on_event("click", value.clone(), |x, _| *x -= 1)
+        .into();
+    let heading: Node = h1!("Value ", value).into();
+    App::render_single(div!(heading.clone(), add.clone(), subtract.clone()).into());
+
+    Memory::dispatch(&add, "click");
+    Memory::dispatch(&add, "click");
+    Memory::dispatch(&subtract, "click");
+    assert_eq!(Memory::text_content(&heading), "Value 1");
+}
+
+#[test]
+fn bubbling() {
+    let clicks = StateAtomic::new(0usize);
+    let inner: Node = span!("Click").into();
+    let page: Node = div!(p!(inner.clone()), clicks.clone())
+        .on_event("click", clicks, |x, _| *x += 1)
+        .into();
+
+    Memory::dispatch(&inner, "click");
+    assert_eq!(Memory::text_content(&page), "Click1");
+}
+
+#[test]
+fn input() {
+    let value = StateMutex::new(String::new());
+    let count = StateAtomic::new(0usize);
+    let field: Node = input!("text")
+        .double_bind(value.clone())
+        .bind_func(count.clone(), |x| x.len())
+        .into();
+    let page: Node = div!(h3!(value.clone()), h3!(count), field.clone()).into();
+
+    Memory::set_value(&field, "Hello");
+    Memory::dispatch(&field, "input");
+    assert_eq!(Memory::text_content(&page), "Hello5");
+
+    value.put(String::from("Hi"));
+    Memory::flush();
+    assert_eq!(Memory::value(&field), "Hi");
+}
+
+#[test]
+fn classes() {
+    let tag = div!("Hello").class("a");
+    let toggle = tag.clone();
+    let button: Node = button!("Toggle")
+        .on_event("click", (), move |_, _| toggle.toggle_class("b"))
+        .into();
+
+    Memory::dispatch(&button, "click");
+    assert_eq!(
+        render_to_string(tag.clone()),
+        "<div class=\"a b\">Hello</div>"
+    );
+
+    Memory::dispatch(&button, "click");
+    tag.rem_class("a");
+    assert_eq!(render_to_string(tag), "<div class=\"\">Hello</div>");
+}
+
+#[test]
+fn list() {
+    let list = StateVec::new();
+    let view: Node = list.view(ul!(), |x| li!(x)).into();
+    let push: Node = button!("Push")
+        .on_event("click", list.clone(), |x, _| {
+            x.push_mutex(x.len().to_string())
+        })
+        .into();
+    let pop: Node = button!("Pop")
+        .on_event("click", list, |x, _| x.pop())
+        .into();
+
+    Memory::dispatch(&push, "click");
+    Memory::dispatch(&push, "click");
+    Memory::dispatch(&push, "click");
+    Memory::dispatch(&pop, "click");
+    assert_eq!(Memory::children(&view).len(), 2);
+    assert_eq!(Memory::text_content(&view), "01");
+}
+
+#[test]
+fn list_api() {
+    let list = (0..5).collect::<StateVec<StateAtomic<usize>>>();
+    let view: Node = list.view(ul!(), |x| li!(x)).into();
+    let nodes = Memory::children(&view);
+    let text = || Memory::text_content(&view);
+
+    list.reverse();
+    assert_eq!(text(), "43210");
+    assert!(Memory::children(&view)[0] == nodes[4]);
+
+    list.sort_by(|a, b| a.value().cmp(&b.value()));
+    assert_eq!(text(), "01234");
+    assert!(Memory::children(&view) == nodes);
+
+    list.swap(0, 4);
+    list.move_item(1, 3);
+    assert_eq!(text(), "42310");
+    assert!(Memory::children(&view)[3] == nodes[1]);
+
+    list.set(2, StateAtomic::new(7));
+    list.retain(|x| x.value() != 1);
+    assert_eq!(text(), "4270");
+    assert!(Memory::children(&view)[0] == nodes[4]);
+
+    list.extend(vec![StateAtomic::new(8), StateAtomic::new(9)]);
+    list.truncate(5);
+    assert_eq!(text(), "42708");
+    assert_eq!(list.len(), 5);
+
+    list.clear();
+    assert_eq!(text(), "");
+    assert!(list.is_empty());
+}
+
+#[test]
+fn replace_all() {
+    let list = (0..5).collect::<StateVec<StateAtomic<usize>>>();
+    let view: Node = list.view(ul!(), |x| li!(x)).into();
+    let nodes = Memory::children(&view);
+
+    let mut values = list.clone().into_iter().collect::<Vec<_>>();
+    values.reverse();
+    values.retain(|x| x.value() % 2 == 0);
+    values.insert(1, StateAtomic::new(7));
+    list.replace_all(values);
+    assert_eq!(Memory::text_content(&view), "4720");
+    assert!(Memory::children(&view)[0] == nodes[4]);
+    assert!(Memory::children(&view)[3] == nodes[0]);
+
+    let rows = StateVec::new();
+    rows.push_mutex(String::from("1 Ada"));
+    rows.push_mutex(String::from("2 Grace"));
+    let view: Node = rows.view(ul!(), |x| li!(x)).into();
+    let nodes = Memory::children(&view);
+    let second = rows.get(1).unwrap();
+
+    let fetched = vec!["3 Alan", "2 Grace Hopper"];
+    rows.replace_all_by_key(
+        fetched
+            .into_iter()
+            .map(|x| StateMutex::new(String::from(x)))
+            .collect(),
+        |x| x.split(' ').next().unwrap_or_default().to_string(),
+    );
+    Memory::flush();
+    assert_eq!(Memory::text_content(&view), "3 Alan2 Grace Hopper");
+    assert!(Memory::children(&view)[1] == nodes[1]);
+    assert!(rows.get(1).unwrap() == second);
+}
+
+#[test]
+fn derived_lists() {
+    let names = StateVec::new();
+    ["Grace", "Ada", "Alan", "Barbara"]
+        .iter()
+        .for_each(|x| names.push_mutex(String::from(*x)));
+
+    let search = StateMutex::new(String::new());
+    let search_move = search.clone();
+    let found = names
+        .watch(&search)
+        .filter(move |x| x.value().contains(search_move.value().as_str()));
+    let sorted = found.sort_by_key(|x| x.value()).take(2);
+    let lengths = names.map(|x| StateAtomic::from(x, |x| x.len()));
+
+    let view: Node = found.view(ul!(), |x| li!(x)).into();
+    let sorted: Node = sorted.view(ul!(), |x| li!(x, " ")).into();
+    let lengths: Node = lengths.view(ul!(), |x| li!(x)).into();
+    let nodes = Memory::children(&view);
+    assert_eq!(Memory::text_content(&sorted), "Ada Alan ");
+
+    search.put(String::from("r"));
+    Memory::flush();
+    assert_eq!(Memory::text_content(&view), "GraceBarbara");
+    assert!(Memory::children(&view)[1] == nodes[3]);
+    assert_eq!(Memory::text_content(&sorted), "Barbara Grace ");
+
+    names.push_mutex(String::from("Katherine"));
+    names.get(0).unwrap().put(String::from("Hopper"));
+    Memory::flush();
+    assert_eq!(Memory::text_content(&lengths), "63479");
+    assert_eq!(Memory::text_content(&view), "HopperBarbaraKatherine");
+}
+
+#[test]
+fn derived_element_changes() {
+    let numbers: StateVec<StateAtomic<i32>> = vec![3, 8, 5].into_iter().collect();
+    let even = numbers.filter(|x| x.value() % 2 == 0);
+    let sorted = numbers.sort_by_key(|x| x.value());
+
+    let even: Node = even.view(ul!(), |x| li!(x, " ")).into();
+    let sorted: Node = sorted.view(ul!(), |x| li!(x, " ")).into();
+    assert_eq!(Memory::text_content(&even), "8 ");
+    assert_eq!(Memory::text_content(&sorted), "3 5 8 ");
+
+    numbers.get(0).unwrap().put(4);
+    Memory::flush();
+    assert_eq!(Memory::text_content(&even), "4 8 ");
+    assert_eq!(Memory::text_content(&sorted), "4 5 8 ");
+
+    numbers.get(1).unwrap().put(1);
+    Memory::flush();
+    assert_eq!(Memory::text_content(&even), "4 ");
+    assert_eq!(Memory::text_content(&sorted), "1 4 5 ");
+}
+
+#[test]
+fn derived_freed() {
+    let numbers: StateVec<StateAtomic<i32>> = vec![1, 2].into_iter().collect();
+    let runs = std::rc::Rc::new(std::cell::Cell::new(0));
+
+    let runs_move = runs.clone();
+    let odd = numbers.filter(move |x| {
+        runs_move.set(runs_move.get() + 1);
+        x.value() % 2 == 1
+    });
+    let view: Node = odd
+        .sort_by_key(|x| x.value())
+        .view(ul!(), |x| li!(x))
+        .into();
+    numbers.push_atomic(3);
+    Memory::flush();
+    assert_eq!(Memory::text_content(&view), "13");
+    let count = runs.get();
+
+    // The view keeps the derived StateVecs updated until it is torn down.
+    drop(odd);
+    numbers.push_atomic(5);
+    Memory::flush();
+    assert_eq!(Memory::text_content(&view), "135");
+    assert!(runs.get() > count);
+
+    view.unmount();
+    Memory::flush();
+    let count = runs.get();
+    numbers.push_atomic(7);
+    numbers.get(0).unwrap().put(9);
+    Memory::flush();
+    assert_eq!(runs.get(), count);
+
+    let lengths = numbers.map(|x| StateAtomic::from(x, |x| x * 10));
+    let first = lengths.get(0).unwrap();
+    let removed = numbers.get(0).unwrap();
+    numbers.remove(0);
+    Memory::flush();
+    assert_eq!(lengths.len(), 4);
+
+    // The state mapped from a removed element is not updated anymore.
+    removed.put(11);
+    Memory::flush();
+    assert_eq!(first.value(), 90);
+}
+
+#[test]
+fn map() {
+    let users = StateMap::new();
+    users.insert(7, StateMutex::new(String::from("Grace")));
+    let view: Node = users.view(ul!(), |id, name| li!(id, " ", name)).into();
+    let first = Memory::children(&view)[0].clone();
+
+    users.insert(3, StateMutex::new(String::from("Ada")));
+    users.insert(9, StateMutex::new(String::from("Alan")));
+    Memory::flush();
+    assert_eq!(Memory::text_content(&view), "3 Ada7 Grace9 Alan");
+    assert!(Memory::children(&view)[1] == first);
+
+    users.get(&7).unwrap().put(String::from("Hopper"));
+    users.remove(&3);
+    users.insert(9, StateMutex::new(String::from("Turing")));
+    assert_eq!(Memory::text_content(&view), "7 Hopper9 Turing");
+    assert!(Memory::children(&view)[0] == first);
+    assert_eq!(users.keys(), [7, 9]);
+
+    // A view torn down stops listening, while the others are still updated.
+    let names: Node = users.view(ol!(), |_, name| li!(name)).into();
+    names.unmount();
+    users.insert(5, StateMutex::new(String::from("Barbara")));
+    Memory::flush();
+    assert_eq!(Memory::text_content(&view), "5 Barbara7 Hopper9 Turing");
+    assert_eq!(Memory::text_content(&names), "HopperTuring");
+}
+
+#[test]
+fn link() {
+    let link: Node = link!("/about", "About").into();
+
+    assert!(Memory::dispatch_event(
+        &link,
+        MemoryEvent::new("click").with_modifier()
+    ));
+    assert_eq!(
+        Memory::attributes(&link),
+        vec![("href".to_string(), "#/about".to_string())]
+    );
+}
+
+#[test]
+fn unmount() {
+    let value = StateAtomic::new(0isize);
+    let button: Node = button!("Add 1")
+        .on_event("click", value.clone(), |x, _| *x += 1)
+        .into();
+    let page: Node = div!(value.clone(), button.clone()).into();
+    App::render_single(page.clone());
+
+    page.unmount();
+    Memory::dispatch(&button, "click");
+    assert_eq!(value.value(), 0);
+    assert_eq!(Memory::children(&Memory::body()).len(), 0);
+}
@@ -0,0 +1,117 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use valerie::backend::Memory;
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::state::{effect, effect_all};
+
+#[test]
+fn from_all() {
+    let price = StateAtomic::new(2);
+    let quantity = StateAtomic::new(3);
+    let total = StateAtomic::from_all((price.clone(), quantity.clone()), |(x, y)| x * y);
+    let page: Node = p!("Total ", total.clone()).into();
+    assert_eq!(Memory::text_content(&page), "Total 6");
+
+    price.put(4);
+    assert_eq!(Memory::text_content(&page), "Total 12");
+
+    quantity.put(10);
+    assert_eq!(Memory::text_content(&page), "Total 40");
+}
+
+#[test]
+fn from_all_at_once() {
+    let first = StateMutex::new(String::from("a"));
+    let second = StateAtomic::new(1);
+    let third = StateAtomic::new(true);
+    let seen = Rc::new(Cell::new(0));
+
+    let seen_move = Rc::clone(&seen);
+    let joined = StateMutex::from_all(
+        (first.clone(), second.clone(), third.clone()),
+        move |(x, y, z)| {
+            seen_move.set(seen_move.get() + 1);
+            format!("{}{}{}", x, y, z)
+        },
+    );
+    assert_eq!(seen.get(), 1);
+
+    first.put(String::from("b"));
+    second.put(2);
+    third.put(false);
+    Memory::flush();
+
+    assert_eq!(joined.value(), "b2false");
+    assert_eq!(seen.get(), 2);
+}
+
+#[test]
+fn batch() {
+    let price = StateAtomic::new(1);
+    let quantity = StateAtomic::new(1);
+    let seen = Rc::new(RefCell::new(Vec::new()));
+
+    let seen_move = Rc::clone(&seen);
+    effect(&price, move |x| seen_move.borrow_mut().push(x)).forget();
+
+    let totals = Rc::new(RefCell::new(Vec::new()));
+    let totals_move = Rc::clone(&totals);
+    effect_all((price.clone(), quantity.clone()), move |(x, y)| {
+        totals_move.borrow_mut().push(x * y)
+    })
+    .forget();
+
+    valerie::state::batch(|| {
+        price.put(2);
+        price.put(3);
+        valerie::state::batch(|| quantity.put(4));
+        Memory::flush();
+        assert!(totals.borrow().is_empty());
+
+        price.put(5);
+    });
+    Memory::flush();
+
+    assert_eq!(*seen.borrow(), [5]);
+    assert_eq!(*totals.borrow(), [20]);
+}
+
+#[test]
+fn put_if_changed() {
+    let value = StateMutex::new(String::from("a"));
+    let seen = Rc::new(Cell::new(0));
+
+    let seen_move = Rc::clone(&seen);
+    effect(&value, move |_| seen_move.set(seen_move.get() + 1)).forget();
+
+    assert!(!value.put_if_changed(String::from("a")));
+    Memory::flush();
+    assert_eq!(seen.get(), 0);
+
+    assert!(value.put_if_changed(String::from("b")));
+    Memory::flush();
+    assert_eq!(seen.get(), 1);
+}
+
+#[test]
+fn distinct() {
+    let text = StateMutex::new(String::new());
+    let even = StateAtomic::from(&text, |x| x.len() % 2 == 0).distinct();
+    let label = StateMutex::from(&even, |x| String::from(if x { "even" } else { "odd" }));
+    let seen = Rc::new(Cell::new(0));
+
+    let seen_move = Rc::clone(&seen);
+    effect(&label, move |_| seen_move.set(seen_move.get() + 1)).forget();
+
+    for x in ["ab", "abcd", "abc", "a"] {
+        text.put(String::from(x));
+        Memory::flush();
+    }
+
+    assert_eq!(label.value(), "odd");
+    assert_eq!(seen.get(), 1);
+}
@@ -0,0 +1,131 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use valerie::backend::Memory;
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::state::{effect, effect_all};
+
+#[test]
+fn runs_on_change() {
+    let name = StateMutex::new(String::from("a"));
+    let seen = Rc::new(RefCell::new(Vec::new()));
+
+    let seen_move = Rc::clone(&seen);
+    let handle = effect(&name, move |x| seen_move.borrow_mut().push(x));
+    Memory::flush();
+    assert!(seen.borrow().is_empty());
+
+    name.put(String::from("b"));
+    Memory::flush();
+    name.put(String::from("c"));
+    Memory::flush();
+    assert_eq!(*seen.borrow(), ["b", "c"]);
+
+    handle.stop();
+    name.put(String::from("d"));
+    Memory::flush();
+    assert_eq!(*seen.borrow(), ["b", "c"]);
+}
+
+#[test]
+fn skips_the_current_value() {
+    let count = StateAtomic::new(0);
+    let name = StateMutex::new(String::from("a"));
+    count.put(1);
+    name.put(String::from("b"));
+    Memory::flush();
+
+    let runs = Rc::new(Cell::new(0));
+    let runs_move = Rc::clone(&runs);
+    let _handle = effect(&count, move |_| runs_move.set(runs_move.get() + 1));
+    let runs_move = Rc::clone(&runs);
+    let _all = effect_all((count.clone(), name.clone()), move |_| {
+        runs_move.set(runs_move.get() + 10)
+    });
+    Memory::flush();
+    assert_eq!(runs.get(), 0);
+
+    count.put(2);
+    Memory::flush();
+    assert_eq!(runs.get(), 11);
+}
+
+#[test]
+fn stops_on_drop() {
+    let count = StateAtomic::new(0);
+    let seen = Rc::new(RefCell::new(Vec::new()));
+
+    {
+        let seen_move = Rc::clone(&seen);
+        let _handle = effect(&count, move |x| seen_move.borrow_mut().push(x));
+        count.put(1);
+        Memory::flush();
+    }
+
+    count.put(2);
+    Memory::flush();
+    assert_eq!(*seen.borrow(), [1]);
+}
+
+#[test]
+fn multiple_states() {
+    let first = StateAtomic::new(1);
+    let second = StateMutex::new(String::from("x"));
+    let seen = Rc::new(RefCell::new(Vec::new()));
+
+    let seen_move = Rc::clone(&seen);
+    let handle = effect_all((first.clone(), second.clone()), move |x| {
+        seen_move.borrow_mut().push(x)
+    });
+
+    first.put(2);
+    second.put(String::from("y"));
+    Memory::flush();
+    second.put(String::from("z"));
+    Memory::flush();
+
+    assert_eq!(
+        *seen.borrow(),
+        [(2, String::from("y")), (2, String::from("z"))]
+    );
+    drop(handle);
+}
+
+struct Dropped(Rc<Cell<bool>>);
+
+impl Drop for Dropped {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn close() {
+    let count = StateAtomic::new(0);
+    let page: Node = p!(count.clone()).into();
+    let dropped = Rc::new(Cell::new(false));
+
+    let guard = Dropped(Rc::clone(&dropped));
+    let _handle = effect(&count, move |_| {
+        let _ = &guard;
+    });
+
+    assert!(count.try_put(1));
+    assert_eq!(Memory::text_content(&page), "1");
+    assert!(!dropped.get());
+
+    count.close();
+    assert!(count.is_closed());
+    assert!(count.tx().send(2).is_err());
+    assert!(!count.try_put(2));
+    assert_eq!(count.value(), 1);
+    assert_eq!(Memory::text_content(&page), "1");
+    assert!(dropped.get());
+
+    count.put(3);
+    assert_eq!(count.value(), 3);
+    assert_eq!(Memory::text_content(&page), "1");
+}
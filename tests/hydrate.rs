@@ -0,0 +1,101 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use valerie::backend::{Backend, Memory};
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::ssr::render_to_string;
+
+fn counter() -> Node {
+    let value = StateAtomic::new(0isize);
+
+    div!(
+        h1!("Value ", value.clone(), ""),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+fn children_first() -> Node {
+    let value = StateAtomic::new(0isize);
+    let button = button!("Add 1").on_event("click", value.clone(), move |x, _| {
+        *x += 1;
+    });
+    let h1 = h1!("Value ", value, "");
+
+    div!(h1, button).into()
+}
+
+fn hello() -> Node {
+    div!(h1!("Hello, World!")).into()
+}
+
+fn root() -> Tag<html::elements::Div> {
+    let root = div!().id("app");
+    App::render_single(root.clone().into());
+    App::render_single_to(counter(), "#app");
+
+    root
+}
+
+#[test]
+fn hydrate() {
+    let root: Node = root().into();
+    let markup = render_to_string(root.clone());
+
+    assert_eq!(App::hydrate_single(counter, "#app"), Ok(()));
+    assert_eq!(render_to_string(root.clone()), markup);
+
+    let page = Memory::children(&root)[0].clone();
+    let button = Memory::children(&page)[1].clone();
+    Memory::dispatch(&button, "click");
+    assert_eq!(Memory::text_content(&page), "Value 1Add 1");
+}
+
+#[test]
+fn creation_order() {
+    let root: Node = root().into();
+    let markup = render_to_string(root.clone());
+
+    assert_eq!(App::hydrate_single(children_first, "#app"), Ok(()));
+    assert_eq!(render_to_string(root.clone()), markup);
+
+    let page = Memory::children(&root)[0].clone();
+    let button = Memory::children(&page)[1].clone();
+    Memory::dispatch(&button, "click");
+    assert_eq!(Memory::text_content(&page), "Value 1Add 1");
+}
+
+#[test]
+fn mismatch() {
+    let root = root();
+
+    let e = App::hydrate_single(hello, "#app").unwrap_err();
+    assert_eq!(e.path(), "div#app > div > h1:nth-child(1), child 1");
+    assert_eq!(
+        e.to_string(),
+        "Hydration mismatch at div#app > div > h1:nth-child(1), child 1: \
+         expected nothing, found text \"0\""
+    );
+    assert_eq!(
+        render_to_string(root),
+        "<div id=\"app\"><div><h1>Hello, World!</h1></div></div>"
+    );
+}
+
+fn pending() -> Node {
+    div!(Node::from_future(|| "Loading...", async { "Loaded" })).into()
+}
+
+#[test]
+fn pending_kept() {
+    let root = div!().id("app");
+    App::render_single(root.clone().into());
+    App::render_single_to(pending(), "#app");
+    let markup = render_to_string(root.clone());
+    assert!(markup.contains("Loaded"));
+
+    assert_eq!(App::hydrate_single(pending, "#app"), Ok(()));
+    assert_eq!(render_to_string(root), markup);
+}
@@ -0,0 +1,43 @@
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn island(id: &str) -> web_sys::Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    let element = document.create_element("div").unwrap();
+    element.set_id(id);
+    element.set_text_content(Some("Server rendered content"));
+    document.body().unwrap().append_child(&element).unwrap();
+
+    element
+}
+
+fn counter() -> Node {
+    let value = StateAtomic::new(0isize);
+
+    div!(
+        h1!("Value ", value.clone()),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+fn hello() -> Node {
+    h1!("Hello, World!").into()
+}
+
+#[wasm_bindgen_test]
+pub fn run() {
+    island("counter");
+    let hello_island = island("hello");
+
+    App::render_single_to(counter(), "#counter");
+    App::render_single_to(hello(), hello_island);
+}
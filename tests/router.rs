@@ -0,0 +1,347 @@
+use wasm_bindgen_test::*;
+
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::router::Guard;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn home() -> Node {
+    div!(
+        h1!("Home"),
+        link!("/counter", "Counter"),
+        link!("/counter/10", "Counter from 10"),
+        button!("Counter").on_event("click", (), |_, _| {
+            App::navigate("/counter");
+        })
+    )
+    .into()
+}
+
+fn layout(outlet: Node) -> Node {
+    let visits = StateAtomic::new(0usize);
+
+    div!(
+        p!("Counter pages visited ", visits.clone()),
+        button!("Visit").on_event("click", visits, |x, _| {
+            *x += 1;
+        }),
+        outlet
+    )
+    .into()
+}
+
+fn counter(start: isize) -> Node {
+    let value = StateAtomic::new(start);
+
+    div!(
+        h1!("Value ", value.clone()),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+async fn report() -> Node {
+    wasm_timer::Delay::new(core::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    h1!("Report loaded").into()
+}
+
+fn loading() -> Node {
+    p!("Loading...").into()
+}
+
+fn not_found() -> Node {
+    h1!("Page not found").into()
+}
+
+#[wasm_bindgen_test]
+pub fn run() {
+    App::new()
+        .push("/", home)
+        .layout("/counter", layout)
+        .push("/counter", || counter(0))
+        .push_params("/counter/:start", |params| {
+            Some(counter(params.get("start")?))
+        })
+        .push_async("/report", report)
+        .loading(loading)
+        .redirect("/count/:start", "/counter/:start")
+        .guard("/counter", |path| {
+            if path.ends_with("/13") {
+                Guard::Redirect("/counter".to_string())
+            } else {
+                Guard::Allow
+            }
+        })
+        .not_found(not_found)
+        .start("/")
+        .render();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn routes() -> App {
+    let mut app = App::new();
+    app.push("/", || h1!("Home").into())
+        .push("/about", || h1!("About").into())
+        .push("/about/team", || h1!("Team").into())
+        .push_params("/users/:id", |params| {
+            Some(h1!("User ", params.get::<u32>("id")?).into())
+        })
+        .push_params("/users/:id/posts/:post", |params| {
+            Some(
+                h1!(
+                    "Post ",
+                    params.get_str("post")?.to_string(),
+                    " by ",
+                    params.get::<u32>("id")?
+                )
+                .into(),
+            )
+        })
+        .push_params("/files/*rest", |params| {
+            Some(h1!("File ", params.get_str("rest")?.to_string()).into())
+        })
+        .not_found(not_found);
+    app
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn static_routes() {
+    let app = routes();
+    assert_eq!(app.render_to_string("/").unwrap(), "<h1>Home</h1>");
+    assert_eq!(app.render_to_string("/about").unwrap(), "<h1>About</h1>");
+    assert_eq!(
+        app.render_to_string("/about/team").unwrap(),
+        "<h1>Team</h1>"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn param_routes() {
+    let app = routes();
+    assert_eq!(
+        app.render_to_string("/users/7").unwrap(),
+        "<h1>User <!---->7</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/users/7/posts/intro").unwrap(),
+        "<h1>Post <!---->intro<!----> by <!---->7</h1>"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn trailing_slashes() {
+    let app = routes();
+    assert_eq!(app.render_to_string("/about/").unwrap(), "<h1>About</h1>");
+    assert_eq!(
+        app.render_to_string("/about//team/").unwrap(),
+        "<h1>Team</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/users/7/").unwrap(),
+        "<h1>User <!---->7</h1>"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn empty_path() {
+    let app = routes();
+    assert_eq!(app.render_to_string("").unwrap(), "<h1>Home</h1>");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn not_found_page() {
+    let app = routes();
+    assert_eq!(
+        app.render_to_string("/missing").unwrap(),
+        "<h1>Page not found</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/about/x").unwrap(),
+        "<h1>Page not found</h1>"
+    );
+
+    // Without a `not_found` page, the starting route is rendered instead.
+    let mut app = App::new();
+    app.push("/", || h1!("Home").into())
+        .push("/about", || h1!("About").into())
+        .start("/");
+    assert_eq!(app.render_to_string("/missing").unwrap(), "<h1>Home</h1>");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn typed_params() {
+    let app = routes();
+    assert_eq!(
+        app.render_to_string("/users/abc").unwrap(),
+        "<h1>Page not found</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/users/-1").unwrap(),
+        "<h1>Page not found</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/users/%37").unwrap(),
+        "<h1>User <!---->7</h1>"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn rest_decoded() {
+    let app = routes();
+    assert_eq!(
+        app.render_to_string("/files/a%20b/c%2Fd.txt").unwrap(),
+        "<h1>File <!---->a b/c/d.txt</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/files/caf%C3%A9").unwrap(),
+        "<h1>File <!---->café</h1>"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn invalid_escapes() {
+    let app = routes();
+    assert_eq!(
+        app.render_to_string("/files/%zz").unwrap(),
+        "<h1>File <!---->%zz</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/files/100%").unwrap(),
+        "<h1>File <!---->100%</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/files/%4").unwrap(),
+        "<h1>File <!---->%4</h1>"
+    );
+    assert_eq!(
+        app.render_to_string("/files/%+1").unwrap(),
+        "<h1>File <!---->%+1</h1>"
+    );
+    // A segment which is not valid UTF-8 once decoded is kept as it is.
+    assert_eq!(
+        app.render_to_string("/files/a%FFb").unwrap(),
+        "<h1>File <!---->a%FFb</h1>"
+    );
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn guarded() -> App {
+    let mut app = routes();
+    app.redirect("/profile/:id", "/users/:id")
+        .redirect("/loop", "/loop")
+        .guard("/users/0", |_| Guard::Deny)
+        .guard("/about", |path| {
+            if path.ends_with("/secret") {
+                Guard::Redirect("/about".to_string())
+            } else {
+                Guard::Allow
+            }
+        });
+    app
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn redirects() {
+    let app = guarded();
+    assert_eq!(app.resolve("/profile/7").as_deref(), Some("/users/7"));
+    assert_eq!(app.resolve("/users/7").as_deref(), Some("/users/7"));
+    assert_eq!(app.resolve("/loop"), None);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn guards() {
+    let app = guarded();
+    assert_eq!(app.resolve("/users/0"), None);
+    assert_eq!(app.resolve("/users/0/posts/intro"), None);
+    assert_eq!(app.resolve("/profile/0"), None);
+    assert_eq!(app.resolve("/about/team").as_deref(), Some("/about/team"));
+    assert_eq!(app.resolve("/about/secret").as_deref(), Some("/about"));
+    assert_eq!(app.resolve("/").as_deref(), Some("/"));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn modes() {
+    use valerie::router::Mode;
+    use valerie::ssr::render_to_string;
+
+    let mut hash = App::new();
+    hash.push("/", || link!("/about", "About").into());
+    let mut history = App::new();
+    history
+        .push("/", || link!("/about", "About").into())
+        .mode(Mode::History);
+
+    let hash_link = r##"<a href="#/about">About</a>"##;
+    let history_link = r#"<a href="/about">About</a>"#;
+    assert_eq!(history.render_to_string("/").unwrap(), history_link);
+    assert_eq!(hash.render_to_string("/").unwrap(), hash_link);
+    assert_eq!(history.render_to_string("/").unwrap(), history_link);
+    // Outside of an `App`, the links use `Mode::Hash`.
+    assert_eq!(render_to_string(link!("/about", "About")), hash_link);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn query_freed() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use valerie::backend::Memory;
+
+    // The binding stops when the channel is closed.
+    let page = StateAtomic::query("page", 1usize);
+    let pointer = page.pointer();
+    Memory::flush();
+    assert_eq!(Arc::strong_count(&pointer), 3);
+    page.close();
+    Memory::flush();
+    assert_eq!(Arc::strong_count(&pointer), 2);
+
+    // Or when the page it was created for is torn down.
+    let pointer = Rc::new(RefCell::new(None));
+    let pointer_move = Rc::clone(&pointer);
+    let mut app = App::new();
+    app.push("/", move || {
+        let page = StateAtomic::query("page", 1usize);
+        pointer_move.replace(Some(page.pointer()));
+        p!("Page ", page).into()
+    });
+    app.render_to_string("/").unwrap();
+
+    let pointer = pointer.borrow_mut().take().unwrap();
+    assert_eq!(Arc::strong_count(&pointer), 1);
+
+    // Also when it is created while an async page is loading.
+    let pointer = Rc::new(RefCell::new(None));
+    let pointer_move = Rc::clone(&pointer);
+    let mut app = App::new();
+    app.push_async("/", move || {
+        let pointer = Rc::clone(&pointer_move);
+        async move {
+            let page = StateAtomic::query("page", 1usize);
+            pointer.replace(Some(page.pointer()));
+            p!("Page ", page).into()
+        }
+    });
+    assert_eq!(app.render_to_string("/").unwrap(), "<p>Page <!---->1</p>");
+
+    let pointer = pointer.borrow_mut().take().unwrap();
+    assert_eq!(Arc::strong_count(&pointer), 1);
+}
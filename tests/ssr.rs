@@ -0,0 +1,267 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+use valerie::ssr::render_to_string;
+
+fn counter(value: StateAtomic<isize>) -> Node {
+    div!(
+        h1!("Value ", value.clone()),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+#[test]
+fn hello_world() {
+    assert_eq!(render_to_string(h1!("Hello World")), "<h1>Hello World</h1>");
+}
+
+#[test]
+fn state() {
+    let value = StateAtomic::new(0isize);
+    assert_eq!(
+        render_to_string(counter(value.clone())),
+        "<div><h1>Value <!---->0</h1><button>Add 1</button></div>"
+    );
+
+    let page = counter(value.clone());
+    value.put(-5);
+    assert_eq!(
+        render_to_string(page),
+        "<div><h1>Value <!---->-5</h1><button>Add 1</button></div>"
+    );
+}
+
+#[test]
+fn freed() {
+    let value = StateAtomic::new(0isize);
+    let count = || std::sync::Arc::strong_count(&value.pointer());
+    let before = count();
+
+    render_to_string(counter(value.clone()));
+    assert_eq!(count(), before);
+
+    let chunks = valerie::ssr::render_to_stream(counter(value.clone()));
+    assert!(count() > before);
+    drop(chunks);
+    assert_eq!(count(), before);
+}
+
+#[test]
+fn attributes() {
+    let name = StateMutex::new(String::from("a\"b"));
+    let page = div!(br!(), input!("text").double_bind(name.clone()))
+        .id("form")
+        .class("b")
+        .class("a")
+        .attr("title", name.clone());
+
+    name.put(String::from("<x> & 'y'"));
+    assert_eq!(
+        render_to_string(page),
+        "<div id=\"form\" class=\"a b\" title=\"&lt;x&gt; &amp; &#39;y&#39;\">\
+         <br><input type=\"text\" value=\"&lt;x&gt; &amp; &#39;y&#39;\"></div>"
+    );
+}
+
+#[test]
+fn escape_text() {
+    assert_eq!(
+        render_to_string(p!("1 < 2 && \"3\" > 2")),
+        "<p>1 &lt; 2 &amp;&amp; \"3\" &gt; 2</p>"
+    );
+}
+
+#[test]
+fn list() {
+    let list = StateVec::new();
+    list.push(StateAtomic::new(1isize));
+    let page = list.view(ul!(), |x| li!(x));
+
+    list.push(StateAtomic::new(2isize));
+    list.push(StateAtomic::new(3isize));
+    list.remove(0);
+    assert_eq!(render_to_string(page), "<ul><li>2</li><li>3</li></ul>");
+}
+
+#[test]
+fn generate() {
+    use valerie::router::Mode;
+    use valerie::ssr::Document;
+
+    fn home() -> Node {
+        div!(h1!("Home"), link!("/docs/about", "About")).into()
+    }
+
+    fn user(id: u32) -> Node {
+        h1!("User ", id).into()
+    }
+
+    let dir = std::env::temp_dir().join(format!("valerie-ssg-{}", std::process::id()));
+    App::new()
+        .push("/", home)
+        .push("/docs/about", || p!("About").into())
+        .push_params("/users/:id", |x| Some(user(x.get("id")?)))
+        .push("/admin", || h1!("Admin").into())
+        .guard("/admin", |_| panic!("guards are not run while generating"))
+        .layout("/docs", |outlet| div!(h2!("Docs"), outlet).into())
+        .not_found(|| div!(h1!("Not found"), link!("/", "Home")).into())
+        .mode(Mode::History)
+        .generate(&dir, &Document::new().title("Site"))
+        .unwrap();
+
+    let read = |x: &str| std::fs::read_to_string(dir.join(x)).unwrap();
+    assert!(read("index.html")
+        .contains("<body><div><h1>Home</h1><a href=\"/docs/about\">About</a></div></body>"));
+    assert!(read("docs/about/index.html")
+        .contains("<body><div><h2>Docs</h2><div data-outlet=\"\"><p>About</p></div></div></body>"));
+    assert!(read("404.html").contains("<title>Site</title>"));
+    assert!(read("404.html")
+        .contains("<body><div><h1>Not found</h1><a href=\"/\">Home</a></div></body>"));
+    assert!(!dir.join("users").exists());
+    assert!(!dir.join("admin").exists());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn block_on<T>(future: impl std::future::Future<Output = T>) -> T {
+    struct Unpark(std::thread::Thread);
+
+    impl std::task::Wake for Unpark {
+        fn wake(self: std::sync::Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = std::task::Waker::from(std::sync::Arc::new(Unpark(std::thread::current())));
+    let mut future = Box::pin(future);
+    loop {
+        match future
+            .as_mut()
+            .poll(&mut std::task::Context::from_waker(&waker))
+        {
+            std::task::Poll::Ready(x) => return x,
+            std::task::Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+fn delayed(millis: u64, text: &'static str) -> Node {
+    Node::from_future(|| p!("Loading..."), async move {
+        wasm_timer::Delay::new(std::time::Duration::from_millis(millis))
+            .await
+            .unwrap();
+        p!(text)
+    })
+}
+
+#[test]
+fn stream() {
+    let page = div!(delayed(300, "Slow"), delayed(10, "Fast"));
+    let mut chunks = valerie::ssr::Document::new().render_stream(page);
+
+    let chunks = block_on(async move {
+        let mut all = Vec::new();
+        while let Some(x) = chunks.next().await {
+            all.push(x);
+        }
+        all
+    });
+
+    assert_eq!(chunks.len(), 4);
+    assert!(chunks[0].starts_with("<!doctype html>"));
+    assert!(chunks[0].contains(
+        "<div><div style=\"display: contents\" data-pending=\"1\"><p>Loading...</p></div>\
+         <div style=\"display: contents\" data-pending=\"2\"><p>Loading...</p></div></div>"
+    ));
+    assert!(chunks[0].contains("function valerieResolve"));
+    assert_eq!(
+        chunks[1],
+        "<template data-resolved=\"2\"><p>Fast</p></template><script>valerieResolve(2)</script>"
+    );
+    assert_eq!(
+        chunks[2],
+        "<template data-resolved=\"1\"><p>Slow</p></template><script>valerieResolve(1)</script>"
+    );
+    assert_eq!(chunks[3], "</body>\n</html>\n");
+}
+
+#[test]
+fn stream_nested() {
+    let page = Node::from_future(|| "Loading...", async {
+        div!("Outer ", delayed(10, "Inner"))
+    });
+    let mut chunks = valerie::ssr::render_to_stream(page);
+
+    let chunks = block_on(async move {
+        let mut all = Vec::new();
+        while let Some(x) = chunks.next().await {
+            all.push(x);
+        }
+        all
+    });
+
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[0].starts_with(
+        "<div style=\"display: contents\"><div>Outer <div style=\"display: contents\" \
+         data-pending=\"1\"><p>Loading...</p></div></div></div>"
+    ));
+    assert_eq!(
+        chunks[1],
+        "<template data-resolved=\"1\"><p>Inner</p></template><script>valerieResolve(1)</script>"
+    );
+}
+
+#[test]
+fn streams_together() {
+    use std::future::Future;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+    use valerie::ssr::Chunks;
+
+    struct Woken(AtomicBool, std::thread::Thread);
+
+    impl Wake for Woken {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst);
+            self.1.unpark();
+        }
+    }
+
+    fn poll(chunks: &mut Chunks, woken: &Arc<Woken>) -> Poll<Option<String>> {
+        let waker = Waker::from(Arc::clone(woken));
+        let mut next = Box::pin(chunks.next());
+        next.as_mut().poll(&mut Context::from_waker(&waker))
+    }
+
+    let mut a = valerie::ssr::render_to_stream(delayed(10, "A"));
+    let mut b = valerie::ssr::render_to_stream(delayed(10, "B"));
+    let woken_a = Arc::new(Woken(AtomicBool::new(false), std::thread::current()));
+    let woken_b = Arc::new(Woken(AtomicBool::new(false), std::thread::current()));
+
+    assert!(matches!(poll(&mut a, &woken_a), Poll::Ready(Some(_))));
+    assert!(matches!(poll(&mut b, &woken_b), Poll::Ready(Some(_))));
+    assert!(poll(&mut a, &woken_a).is_pending());
+    assert!(poll(&mut b, &woken_b).is_pending());
+
+    // Both streams are woken when their futures resolve.
+    let start = std::time::Instant::now();
+    let both = || woken_a.0.load(Ordering::SeqCst) && woken_b.0.load(Ordering::SeqCst);
+    while !both() && start.elapsed() < std::time::Duration::from_secs(5) {
+        std::thread::park_timeout(std::time::Duration::from_millis(10));
+    }
+    assert!(woken_a.0.load(Ordering::SeqCst));
+    assert!(woken_b.0.load(Ordering::SeqCst));
+
+    match (poll(&mut a, &woken_a), poll(&mut b, &woken_b)) {
+        (Poll::Ready(Some(a)), Poll::Ready(Some(b))) => {
+            assert!(a.contains("<p>A</p>"));
+            assert!(b.contains("<p>B</p>"));
+        }
+        x => panic!("the streams are not resolved: {:?}", x),
+    }
+}
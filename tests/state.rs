@@ -48,6 +48,28 @@ fn state_vec_ui() -> impl Component {
     )
 }
 
+fn state_map_ui() -> impl Component {
+    let map = StateMap::new();
+    map.insert(2, StateMutex::new(String::from("Two")));
+    map.insert(1, StateMutex::new(String::from("One")));
+
+    map.view(ul!(), |k, v| li!(k, ": ", v))
+}
+
+fn state_query_ui() -> impl Component {
+    let search = StateMutex::query("search", String::new());
+    let page = StateAtomic::query("page", 1usize);
+
+    div!(
+        input!("text").double_bind(search.clone()),
+        h3!("Search ", search),
+        h3!("Page ", page.clone()),
+        button!("Next page").on_event("click", page, |x, _| {
+            *x += 1;
+        })
+    )
+}
+
 fn ui() -> Node {
     div!(
         "StateAtomic from",
@@ -69,6 +91,16 @@ fn ui() -> Node {
         br!(),
         state_vec_ui(),
         br!(),
+        br!(),
+        "StateMap",
+        br!(),
+        state_map_ui(),
+        br!(),
+        br!(),
+        "Query string",
+        br!(),
+        state_query_ui(),
+        br!(),
         br!()
     )
     .into()
@@ -0,0 +1,37 @@
+use wasm_bindgen_test::*;
+
+use valerie::prelude::components::*;
+use valerie::prelude::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+fn counter(value: StateAtomic<isize>) -> Node {
+    div!(
+        h1!("Value ", value.clone()),
+        button!("Add 1").on_event("click", value, move |x, _| {
+            *x += 1;
+        })
+    )
+    .into()
+}
+
+#[wasm_bindgen_test]
+pub fn run() {
+    let value = StateAtomic::new(0isize);
+    let page = counter(value.clone());
+    App::render_single(page.clone());
+
+    page.unmount();
+    value.put(1);
+}
+
+#[wasm_bindgen_test]
+pub fn list() {
+    let list = StateVec::new();
+    App::render_single(list.view(div!(), counter).into());
+
+    list.push(StateAtomic::new(0isize));
+    list.push(StateAtomic::new(1isize));
+    list.remove(0);
+    list.pop();
+}